@@ -25,7 +25,7 @@ impl Basic {
 }
 
 impl System for Basic {
-    fn update(&mut self, _delta: &Timing, _: &Window) {
+    fn update(&mut self, _delta: &Timing, _: Option<&Window>) {
         if self.greyscale < 255 {
             self.greyscale += 1;
         } else {
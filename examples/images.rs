@@ -5,7 +5,6 @@ use winit::keyboard::KeyCode;
 
 /// This example shows how to load, display and alter an image
 /// It also shows an example of how to use delta
-
 fn main() -> Result<()> {
     let width = 300;
     let height = 300;
@@ -80,7 +79,7 @@ impl ImageScene {
 }
 
 impl System for ImageScene {
-    fn update(&mut self, timing: &Timing, _: &Window) {
+    fn update(&mut self, timing: &Timing, _: Option<&Window>) {
         let sw = self.width;
         let sh = self.height;
 
@@ -159,10 +159,14 @@ impl Scene<SceneResult, SceneName> for LayoutTest {
         self.rightbottom.render(graphics, mouse);
     }
 
-    fn on_key_up(&mut self, key: KeyCode, _: &MouseData, held: &FxHashSet<KeyCode>) {
-        self.text_field.on_key_press(key, held);
-        self.padding.on_key_press(key, held);
-        self.spacing.on_key_press(key, held);
+    fn on_key_down(&mut self, key: KeyCode, mouse: &MouseData, _: &FxHashSet<KeyCode>) {
+        self.text_field.on_key_press(key, mouse.modifiers);
+        self.padding.on_key_press(key, mouse.modifiers);
+        self.spacing.on_key_press(key, mouse.modifiers);
+    }
+
+    fn on_key_repeat(&mut self, key: KeyCode, mouse: &MouseData, held: &FxHashSet<KeyCode>) {
+        self.on_key_down(key, mouse, held);
     }
 
     fn on_mouse_click(
@@ -218,7 +222,7 @@ impl Scene<SceneResult, SceneName> for LayoutTest {
         timing: &Timing,
         _: &MouseData,
         _: &FxHashSet<KeyCode>,
-        _: &Window,
+        _: Option<&Window>,
     ) -> SceneUpdateResult<SceneResult, SceneName> {
         self.text_field.update(timing);
         self.spacing.update(timing);
@@ -36,7 +36,7 @@ impl Scene<SR, SN> for WhiteTextScene {
         _: &Timing,
         _: &MouseData,
         _: &FxHashSet<KeyCode>,
-        _: &Window,
+        _: Option<&Window>,
     ) -> SceneUpdateResult<SR, SN> {
         Nothing
     }
@@ -87,7 +87,7 @@ impl PrePost<SR, SN> for ExtrasImpl {
         _: &MouseData,
         _: &FxHashSet<KeyCode>,
         _: &mut [Box<dyn Scene<SR, SN>>],
-        _: &Window,
+        _: Option<&Window>,
     ) {
     }
 
@@ -97,7 +97,7 @@ impl PrePost<SR, SN> for ExtrasImpl {
         _: &MouseData,
         _: &FxHashSet<KeyCode>,
         _: &mut [Box<dyn Scene<SR, SN>>],
-        _: &Window,
+        _: Option<&Window>,
     ) {
         if self.timer.update(timing) {
             self.pixel.x += 1;
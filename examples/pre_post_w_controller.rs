@@ -41,7 +41,7 @@ impl Scene<SR, SN> for WhiteTextScene {
         _: &MouseData,
         _: &FxHashSet<KeyCode>,
         _: &GameController,
-        _: &Window,
+        _: Option<&Window>,
     ) -> SceneUpdateResult<SR, SN> {
         Nothing
     }
@@ -95,7 +95,7 @@ impl PrePost<SR, SN> for ExtrasImpl {
         _: &FxHashSet<KeyCode>,
         _: &mut [Box<dyn Scene<SR, SN>>],
         _: &GameController,
-        _: &Window,
+        _: Option<&Window>,
     ) {
     }
 
@@ -106,7 +106,7 @@ impl PrePost<SR, SN> for ExtrasImpl {
         _: &FxHashSet<KeyCode>,
         _: &mut [Box<dyn Scene<SR, SN>>],
         _: &GameController,
-        _: &Window,
+        _: Option<&Window>,
     ) {
         if self.timer.update(timing) {
             self.pixel.x += 1;
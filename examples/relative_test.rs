@@ -205,7 +205,7 @@ impl Scene<SceneResult, SceneName> for LayoutTest {
         _: &Timing,
         _: &MouseData,
         _: &FxHashSet<KeyCode>,
-        _: &Window,
+        _: Option<&Window>,
     ) -> SceneUpdateResult<SceneResult, SceneName> {
         Nothing
     }
@@ -165,10 +165,14 @@ impl Scene<SceneResult, SceneName> for Menu {
         self.dir_panel.render(graphics, mouse);
     }
 
-    fn on_key_up(&mut self, key: KeyCode, _: &MouseData, held: &FxHashSet<KeyCode>) {
-        self.field1.on_key_press(key, held);
-        self.field2.on_key_press(key, held);
-        self.field3.on_key_press(key, held);
+    fn on_key_down(&mut self, key: KeyCode, mouse: &MouseData, _: &FxHashSet<KeyCode>) {
+        self.field1.on_key_press(key, mouse.modifiers);
+        self.field2.on_key_press(key, mouse.modifiers);
+        self.field3.on_key_press(key, mouse.modifiers);
+    }
+
+    fn on_key_repeat(&mut self, key: KeyCode, mouse: &MouseData, held: &FxHashSet<KeyCode>) {
+        self.on_key_down(key, mouse, held);
     }
 
     fn on_mouse_click(
@@ -209,7 +213,7 @@ impl Scene<SceneResult, SceneName> for Menu {
         timing: &Timing,
         _: &MouseData,
         _: &FxHashSet<KeyCode>,
-        _: &Window,
+        _: Option<&Window>,
     ) -> SceneUpdateResult<SceneResult, SceneName> {
         self.field1.update(timing);
         self.field2.update(timing);
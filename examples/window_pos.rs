@@ -51,7 +51,7 @@ impl System for WindowPrefsScene {
         )
     }
 
-    fn update(&mut self, _delta: &Timing, _: &Window) {
+    fn update(&mut self, _delta: &Timing, _: Option<&Window>) {
         if self.idx < self.colors.len() - 1 {
             self.idx += 1;
         } else {
@@ -0,0 +1,466 @@
+use crate::prelude::*;
+#[cfg(feature = "images")]
+use log::error;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "images")]
+use std::fs::File;
+#[cfg(feature = "images")]
+use std::io::{BufWriter, Write};
+#[cfg(feature = "images")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "images")]
+use std::sync::mpsc::{channel, Sender};
+#[cfg(feature = "images")]
+use std::thread::JoinHandle;
+#[cfg(feature = "images")]
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Shortest frame delay most GIF viewers will respect, shorter frames are merged into the next one
+#[cfg(feature = "images")]
+const MIN_GIF_DELAY: Duration = Duration::from_millis(20);
+
+/// What a screenshot should contain
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ScreenshotSource {
+    /// The canvas at its logical size, exactly as drawn by [System::render]
+    Canvas,
+    /// The window contents after scaling
+    Window,
+}
+
+/// Screenshot hotkey settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenshotOptions {
+    /// Key that triggers the screenshot
+    pub key: KeyCode,
+    pub source: ScreenshotSource,
+    /// If set screenshots will be saved to this directory as PNGs
+    /// The image will always be passed to [System::on_screenshot]
+    #[cfg(feature = "images")]
+    pub directory: Option<PathBuf>,
+}
+
+impl ScreenshotOptions {
+    pub fn new(key: KeyCode, source: ScreenshotSource) -> Self {
+        Self {
+            key,
+            source,
+            #[cfg(feature = "images")]
+            directory: None,
+        }
+    }
+}
+
+/// Write `image` to `path` as a PNG
+#[cfg(feature = "images")]
+pub fn save_png<P: AsRef<Path>>(image: &Image, path: P) -> Result<(), GraphicsError> {
+    ::image::save_buffer_with_format(
+        path,
+        &rgba_bytes(image),
+        image.width() as u32,
+        image.height() as u32,
+        ::image::ColorType::Rgba8,
+        ::image::ImageFormat::Png,
+    )
+    .map_err(|e| GraphicsError::SavingImage(e.to_string()))
+}
+
+/// Copy an ARGB buffer into an [Image]
+pub(crate) fn argb_image(buffer: &[u32], width: usize, height: usize) -> Image {
+    let pixels = buffer.iter().map(|px| Color::from_argb(*px)).collect();
+    Image::new(pixels, width, height).expect("Buffer is image size")
+}
+
+/// Pass screenshot to the system and, if it was triggered by the hotkey, save it
+#[allow(unused_variables)]
+pub(crate) fn deliver_screenshot(
+    system: &mut dyn System,
+    options: Option<&ScreenshotOptions>,
+    source: ScreenshotSource,
+    from_hotkey: bool,
+    image: Image,
+) {
+    #[cfg(feature = "images")]
+    if from_hotkey {
+        if let Some(dir) = options.and_then(|opts| opts.directory.as_ref()) {
+            let path = dir.join(format!("screenshot_{}.png", unix_millis()));
+            if let Err(e) = save_png(&image, &path) {
+                error!("Unable to save screenshot to {path:?}: {e:?}");
+            }
+        }
+    }
+    system.on_screenshot(source, image);
+}
+
+pub(crate) fn rgba_bytes(image: &Image) -> Vec<u8> {
+    image
+        .pixels()
+        .iter()
+        .flat_map(|color| [color.r, color.g, color.b, color.a])
+        .collect()
+}
+
+#[cfg(feature = "images")]
+fn unix_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_millis())
+        .unwrap_or_default()
+}
+
+/// How recordings are written
+#[cfg(feature = "images")]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RecordingFormat {
+    /// A single looping animated GIF, `recording_<time>.gif`
+    Gif,
+    /// A directory, `recording_<time>`, of numbered PNGs (`frame_00000.png`, etc)
+    ///
+    /// Frame timings are written to `frames.txt` in the
+    /// [ffmpeg concat](https://ffmpeg.org/ffmpeg-formats.html#concat) format, so the video can be made with
+    /// `ffmpeg -f concat -i frames.txt out.mp4`
+    PngSequence,
+}
+
+/// Returned from [System::recording_requested] to start or stop recording
+#[cfg(feature = "images")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RecordingRequest {
+    /// Start recording every rendered canvas frame
+    /// Does nothing if already recording
+    /// # Arguments
+    /// * `0` - Output format
+    /// * `1` - Directory to write the recording to
+    Start(RecordingFormat, PathBuf),
+    /// Stop and finish writing the recording, the result is passed to [System::on_recording_finished]
+    Stop,
+}
+
+/// Recording hotkey settings, pressing the key starts recording and pressing it again stops
+#[cfg(feature = "images")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingOptions {
+    /// Key that toggles recording
+    pub key: KeyCode,
+    pub format: RecordingFormat,
+    /// Directory recordings are written to
+    pub directory: PathBuf,
+}
+
+#[cfg(feature = "images")]
+impl RecordingOptions {
+    pub fn new<P: Into<PathBuf>>(key: KeyCode, format: RecordingFormat, directory: P) -> Self {
+        Self {
+            key,
+            format,
+            directory: directory.into(),
+        }
+    }
+}
+
+#[cfg(feature = "images")]
+enum RecordingWriter {
+    Gif(::image::codecs::gif::GifEncoder<BufWriter<File>>),
+    Png {
+        index: usize,
+        frames: BufWriter<File>,
+    },
+}
+
+/// Writes canvas frames to disk as they are rendered
+///
+/// Each frame is held until the next one arrives so its duration is known, then
+/// encoded on a separate thread so rendering isn't slowed down
+#[cfg(feature = "images")]
+pub(crate) struct Recorder {
+    path: PathBuf,
+    frames: Sender<(Image, Duration)>,
+    /// Taken once the writer has been waited for
+    worker: Option<JoinHandle<Result<(), GraphicsError>>>,
+    pending: Option<(Image, Instant)>,
+    /// Frames shown for less than this are replaced by the next frame
+    min_frame_time: Duration,
+}
+
+#[cfg(feature = "images")]
+impl Recorder {
+    pub fn start(format: RecordingFormat, directory: &Path) -> Result<Self, GraphicsError> {
+        let err = |e: std::io::Error| GraphicsError::SavingImage(e.to_string());
+        std::fs::create_dir_all(directory).map_err(err)?;
+        let name = format!("recording_{}", unix_millis());
+        let (path, writer) = match format {
+            RecordingFormat::Gif => {
+                let path = directory.join(format!("{name}.gif"));
+                let file = BufWriter::new(File::create(&path).map_err(err)?);
+                let mut encoder = ::image::codecs::gif::GifEncoder::new_with_speed(file, 10);
+                encoder
+                    .set_repeat(::image::codecs::gif::Repeat::Infinite)
+                    .map_err(|e| GraphicsError::SavingImage(e.to_string()))?;
+                (path, RecordingWriter::Gif(encoder))
+            }
+            RecordingFormat::PngSequence => {
+                let path = directory.join(name);
+                std::fs::create_dir_all(&path).map_err(err)?;
+                let mut frames =
+                    BufWriter::new(File::create(path.join("frames.txt")).map_err(err)?);
+                writeln!(frames, "ffconcat version 1.0").map_err(err)?;
+                (path, RecordingWriter::Png { index: 0, frames })
+            }
+        };
+        let min_frame_time = match format {
+            RecordingFormat::Gif => MIN_GIF_DELAY,
+            RecordingFormat::PngSequence => Duration::ZERO,
+        };
+        let (frames, received) = channel();
+        let worker_path = path.clone();
+        let worker = std::thread::spawn(move || {
+            let mut writer = writer;
+            for (image, duration) in received {
+                write_frame(&mut writer, &worker_path, image, duration)?;
+            }
+            //the gif trailer is written when the encoder is dropped
+            if let RecordingWriter::Png { frames, .. } = &mut writer {
+                frames
+                    .flush()
+                    .map_err(|e| GraphicsError::SavingImage(e.to_string()))?;
+            }
+            Ok(())
+        });
+        Ok(Self {
+            path,
+            frames,
+            worker: Some(worker),
+            pending: None,
+            min_frame_time,
+        })
+    }
+
+    /// Queue `image`, rendered at `now`, and send the previous frame to be written
+    ///
+    /// # Returns
+    /// Any error from writing earlier frames
+    pub fn add_frame(&mut self, image: Image, now: Instant) -> Result<(), GraphicsError> {
+        match &mut self.pending {
+            Some((previous, at)) if now.duration_since(*at) < self.min_frame_time => {
+                *previous = image;
+            }
+            _ => {
+                if let Some((previous, at)) = self.pending.replace((image, now)) {
+                    if self
+                        .frames
+                        .send((previous, now.duration_since(at)))
+                        .is_err()
+                    {
+                        //the writer only stops early if it failed
+                        return join_writer(self.worker.take());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the last frame and wait for everything to be written
+    ///
+    /// # Returns
+    /// The path of the GIF or PNG directory
+    pub fn finish(mut self, now: Instant) -> Result<PathBuf, GraphicsError> {
+        if let Some((last, at)) = self.pending.take() {
+            //if this fails the writer has stopped and its error is returned below
+            let _ = self.frames.send((last, now.duration_since(at)));
+        }
+        let Recorder {
+            path,
+            frames,
+            worker,
+            ..
+        } = self;
+        //closing the channel lets the writer finish
+        drop(frames);
+        join_writer(worker)?;
+        Ok(path)
+    }
+}
+
+/// Wait for the writer thread to finish and return its result
+#[cfg(feature = "images")]
+fn join_writer(worker: Option<JoinHandle<Result<(), GraphicsError>>>) -> Result<(), GraphicsError> {
+    match worker.map(JoinHandle::join) {
+        Some(Ok(result)) => result,
+        Some(Err(_)) => Err(GraphicsError::SavingImage(String::from(
+            "Recording writer panicked",
+        ))),
+        None => Err(GraphicsError::SavingImage(String::from(
+            "Recording writer has stopped",
+        ))),
+    }
+}
+
+#[cfg(feature = "images")]
+fn write_frame(
+    writer: &mut RecordingWriter,
+    path: &Path,
+    image: Image,
+    duration: Duration,
+) -> Result<(), GraphicsError> {
+    let err = |e: std::io::Error| GraphicsError::SavingImage(e.to_string());
+    match writer {
+        RecordingWriter::Gif(encoder) => {
+            let buffer = ::image::RgbaImage::from_raw(
+                image.width() as u32,
+                image.height() as u32,
+                rgba_bytes(&image),
+            )
+            .ok_or_else(|| GraphicsError::SavingImage(String::from("Invalid frame size")))?;
+            let frame = ::image::Frame::from_parts(
+                buffer,
+                0,
+                0,
+                ::image::Delay::from_saturating_duration(duration.max(MIN_GIF_DELAY)),
+            );
+            encoder
+                .encode_frame(frame)
+                .map_err(|e| GraphicsError::SavingImage(e.to_string()))
+        }
+        RecordingWriter::Png { index, frames } => {
+            let name = format!("frame_{index:05}.png");
+            save_png(&image, path.join(&name))?;
+            *index += 1;
+            writeln!(frames, "file '{name}'").map_err(err)?;
+            writeln!(frames, "duration {:.3}", duration.as_secs_f64()).map_err(err)
+        }
+    }
+}
+
+/// Start or stop recording based on `request` and then record the current frame if needed
+///
+/// Any result or error is passed to [System::on_recording_finished]
+#[cfg(feature = "images")]
+pub(crate) fn update_recording<F: FnOnce() -> Image>(
+    recorder: &mut Option<Recorder>,
+    system: &mut dyn System,
+    request: Option<RecordingRequest>,
+    frame: F,
+    now: Instant,
+) {
+    match request {
+        Some(RecordingRequest::Start(format, directory)) if recorder.is_none() => {
+            match Recorder::start(format, &directory) {
+                Ok(started) => *recorder = Some(started),
+                Err(e) => {
+                    error!("Unable to start recording: {e:?}");
+                    system.on_recording_finished(Err(e));
+                }
+            }
+        }
+        Some(RecordingRequest::Stop) => stop_recording(recorder, system, now),
+        _ => {}
+    }
+    if let Some(active) = recorder {
+        if let Err(e) = active.add_frame(frame(), now) {
+            error!("Unable to record frame: {e:?}");
+            *recorder = None;
+            system.on_recording_finished(Err(e));
+        }
+    }
+}
+
+/// Finish writing the recording, if there is one
+#[cfg(feature = "images")]
+pub(crate) fn stop_recording(
+    recorder: &mut Option<Recorder>,
+    system: &mut dyn System,
+    now: Instant,
+) {
+    if let Some(active) = recorder.take() {
+        let result = active.finish(now);
+        if let Err(e) = &result {
+            error!("Unable to finish recording: {e:?}");
+        }
+        system.on_recording_finished(result);
+    }
+}
+
+#[cfg(all(test, feature = "images"))]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    fn frame(color: Color) -> Image {
+        let mut image = Image::new_blank(4, 4);
+        image.set_pixel(1, 1, color);
+        image
+    }
+
+    #[test]
+    fn png_sequence_writes_frames_and_timing() {
+        let dir = std::env::temp_dir().join(format!("pgl_rec_test_{}", unix_millis()));
+        let start = Instant::now();
+        let mut recorder = Recorder::start(RecordingFormat::PngSequence, &dir).unwrap();
+        recorder.add_frame(frame(RED), start).unwrap();
+        recorder
+            .add_frame(frame(BLUE), start + Duration::from_millis(50))
+            .unwrap();
+        let path = recorder.finish(start + Duration::from_millis(150)).unwrap();
+
+        assert!(path.join("frame_00000.png").exists());
+        assert!(path.join("frame_00001.png").exists());
+        let timing = std::fs::read_to_string(path.join("frames.txt")).unwrap();
+        assert_eq!(
+            timing,
+            "ffconcat version 1.0\nfile 'frame_00000.png'\nduration 0.050\nfile 'frame_00001.png'\nduration 0.100\n"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn gif_is_written() {
+        let dir = std::env::temp_dir().join(format!("pgl_gif_test_{}", unix_millis()));
+        let start = Instant::now();
+        let mut recorder = Recorder::start(RecordingFormat::Gif, &dir).unwrap();
+        recorder.add_frame(frame(RED), start).unwrap();
+        recorder
+            .add_frame(frame(GREEN), start + Duration::from_millis(100))
+            .unwrap();
+        let path = recorder.finish(start + Duration::from_millis(200)).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.starts_with(b"GIF89a"));
+        assert_eq!(bytes.last(), Some(&0x3B));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn short_gif_frames_are_merged() {
+        use ::image::AnimationDecoder;
+
+        let dir = std::env::temp_dir().join(format!("pgl_gif_merge_test_{}", unix_millis()));
+        let start = Instant::now();
+        let mut recorder = Recorder::start(RecordingFormat::Gif, &dir).unwrap();
+        recorder.add_frame(frame(RED), start).unwrap();
+        recorder
+            .add_frame(frame(GREEN), start + Duration::from_millis(10))
+            .unwrap();
+        recorder
+            .add_frame(frame(BLUE), start + Duration::from_millis(100))
+            .unwrap();
+        let path = recorder.finish(start + Duration::from_millis(110)).unwrap();
+
+        let file = std::io::BufReader::new(File::open(&path).unwrap());
+        let frames = ::image::codecs::gif::GifDecoder::new(file)
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        let delays: Vec<(u32, u32)> = frames
+            .iter()
+            .map(|frame| frame.delay().numer_denom_ms())
+            .collect();
+        assert_eq!(delays, vec![(100, 1), (20, 1)]);
+        assert_eq!(frames[0].buffer().get_pixel(1, 1).0, [0, 255, 0, 255]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
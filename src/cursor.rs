@@ -0,0 +1,107 @@
+use crate::prelude::*;
+use buffer_graphics_lib::image::Image;
+use rustc_hash::FxHashMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Which software cursor to draw, see [System::software_cursor]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum CursorKind {
+    #[default]
+    Arrow,
+    Text,
+    Hand,
+    Busy,
+    /// For any other cursors your program uses
+    Custom(usize),
+}
+
+/// Image for a software cursor
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CursorImage {
+    pub image: Image,
+    /// The pixel in `image` that is drawn at the mouse position
+    pub hotspot: Coord,
+}
+
+impl CursorImage {
+    pub fn new<P: Into<Coord>>(image: Image, hotspot: P) -> Self {
+        Self {
+            image,
+            hotspot: hotspot.into(),
+        }
+    }
+}
+
+/// Cursor images drawn by the runner at the mouse position after [System::render],
+/// they're drawn on the canvas so scale with it
+///
+/// See [Options::software_cursor]
+///
+/// # Usage
+///
+/// ```
+///# use pixels_graphics_lib::prelude::*;
+///# fn example(arrow: Image, hand: Image) {
+/// let cursors = SoftwareCursor::new()
+///     .with(CursorKind::Arrow, CursorImage::new(arrow, (0, 0)))
+///     .with(CursorKind::Hand, CursorImage::new(hand, (3, 0)));
+///# }
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct SoftwareCursor {
+    cursors: FxHashMap<CursorKind, CursorImage>,
+}
+
+impl SoftwareCursor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, kind: CursorKind, cursor: CursorImage) -> Self {
+        self.register(kind, cursor);
+        self
+    }
+
+    /// Set the image for `kind`, replacing any previous image
+    pub fn register(&mut self, kind: CursorKind, cursor: CursorImage) {
+        self.cursors.insert(kind, cursor);
+    }
+
+    pub fn get(&self, kind: CursorKind) -> Option<&CursorImage> {
+        self.cursors.get(&kind)
+    }
+
+    /// Draw the cursor for `kind` with its hotspot at `xy`
+    ///
+    /// If there's no image for `kind` then [Arrow][CursorKind::Arrow] is used, if that's also missing nothing is drawn
+    pub fn render(&self, graphics: &mut Graphics, kind: CursorKind, xy: Coord) {
+        if let Some(cursor) = self.get(kind).or_else(|| self.get(CursorKind::Arrow)) {
+            graphics.draw_image(xy - cursor.hotspot, &cursor.image);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn draws_at_hotspot_with_fallback() {
+        let image = Image::new(vec![RED; 4], 2, 2).unwrap();
+        let cursors =
+            SoftwareCursor::new().with(CursorKind::Arrow, CursorImage::new(image, (1, 1)));
+        let mut buffer = Graphics::create_buffer_u32(4, 4);
+        let mut graphics = Graphics::new_u32_argb(&mut buffer, 4, 4).unwrap();
+        cursors.render(&mut graphics, CursorKind::Hand, coord!(2, 2));
+        let red = RED.to_argb();
+        #[rustfmt::skip]
+        assert_eq!(buffer, vec![
+            0, 0, 0, 0,
+            0, red, red, 0,
+            0, red, red, 0,
+            0, 0, 0, 0,
+        ]);
+    }
+}
@@ -133,6 +133,16 @@ where
         self.current_dir_field.update(timing);
         self.result.clone()
     }
+
+    /// Return a file as the result, or show the contents of a folder
+    fn open_entry(&mut self, entry: DirResult) {
+        if entry.is_file {
+            self.result = SceneUpdateResult::Pop(Some(SR::load_file_result(entry.path)))
+        } else {
+            self.current_dir_field.set_content(&entry.path);
+            self.dir_panel.set_dir(self.current_dir_field.content());
+        }
+    }
 }
 
 impl<SR: Clone + Debug + PartialEq, SN: Clone + Debug + PartialEq> Scene<SR, SN>
@@ -156,17 +166,21 @@ where
         self.render(graphics, mouse)
     }
 
-    fn on_key_up(&mut self, key: KeyCode, _: &MouseData, held_keys: &FxHashSet<KeyCode>) {
+    fn on_key_down(&mut self, key: KeyCode, mouse: &MouseData, _: &FxHashSet<KeyCode>) {
         if self.current_dir_field.is_focused() {
             if key == KeyCode::KeyV {
-                if held_keys.contains(&KeyCode::ControlRight) {}
+                if mouse.modifiers.ctrl {}
             } else if key == KeyCode::Enter {
             } else {
-                self.current_dir_field.on_key_press(key, held_keys);
+                self.current_dir_field.on_key_press(key, mouse.modifiers);
             }
         }
     }
 
+    fn on_key_repeat(&mut self, key: KeyCode, mouse: &MouseData, held_keys: &FxHashSet<KeyCode>) {
+        self.on_key_down(key, mouse, held_keys);
+    }
+
     fn on_mouse_click(
         &mut self,
         down_at: Coord,
@@ -210,20 +224,32 @@ where
         }
         self.current_dir_field.on_mouse_click(down_at, mouse.xy);
         if let Some(result) = self.dir_panel.on_mouse_click(down_at, mouse.xy) {
-            if result.is_file {
-                self.dir_panel.set_highlight(&result.path);
-            } else {
-                self.current_dir_field.set_content(&result.path);
-                self.dir_panel.set_dir(self.current_dir_field.content());
-            }
+            self.dir_panel.set_highlight(&result.path);
         }
         if self.open.on_mouse_click(down_at, mouse.xy) {
             if let Some(entry) = self.dir_panel.highlighted() {
-                self.result = SceneUpdateResult::Pop(Some(SR::load_file_result(entry.path)))
+                self.open_entry(entry);
             }
         }
     }
 
+    fn on_mouse_multi_click(
+        &mut self,
+        _: Coord,
+        mouse: &MouseData,
+        button: MouseButton,
+        count: usize,
+        _: &FxHashSet<KeyCode>,
+    ) {
+        if button != MouseButton::Left || count != 2 {
+            return;
+        }
+        self.current_dir_field.on_mouse_double_click(mouse.xy);
+        if let Some(entry) = self.dir_panel.on_mouse_double_click(mouse.xy) {
+            self.open_entry(entry);
+        }
+    }
+
     fn on_scroll(&mut self, mouse: &MouseData, _: isize, y_diff: isize, _: &FxHashSet<KeyCode>) {
         self.dir_panel.on_scroll(mouse.xy, y_diff);
     }
@@ -235,7 +261,7 @@ where
         _: &MouseData,
         _: &FxHashSet<KeyCode>,
         _: &GameController,
-        _: &Window,
+        _: Option<&Window>,
     ) -> SceneUpdateResult<SR, SN> {
         self.update(timing)
     }
@@ -246,7 +272,7 @@ where
         timing: &Timing,
         _: &MouseData,
         _: &FxHashSet<KeyCode>,
-        _: &Window,
+        _: Option<&Window>,
     ) -> SceneUpdateResult<SR, SN> {
         self.update(timing)
     }
@@ -179,9 +179,13 @@ where
         self.render(graphics, mouse)
     }
 
-    fn on_key_up(&mut self, key: KeyCode, _: &MouseData, held_keys: &FxHashSet<KeyCode>) {
-        self.name_field.on_key_press(key, held_keys);
-        self.current_dir_field.on_key_press(key, held_keys);
+    fn on_key_down(&mut self, key: KeyCode, mouse: &MouseData, _: &FxHashSet<KeyCode>) {
+        self.name_field.on_key_press(key, mouse.modifiers);
+        self.current_dir_field.on_key_press(key, mouse.modifiers);
+    }
+
+    fn on_key_repeat(&mut self, key: KeyCode, mouse: &MouseData, held_keys: &FxHashSet<KeyCode>) {
+        self.on_key_down(key, mouse, held_keys);
     }
 
     fn on_mouse_click(
@@ -208,8 +212,7 @@ where
                     .to_string();
                 self.name_field.set_content(&filename);
             } else {
-                self.dir_panel.set_dir(&result.path);
-                self.current_dir_field.set_content(&result.path);
+                self.dir_panel.set_highlight(&result.path);
             }
         }
         if self.downloads.on_mouse_click(down_at, mouse.xy) {
@@ -251,6 +254,27 @@ where
         }
     }
 
+    fn on_mouse_multi_click(
+        &mut self,
+        _: Coord,
+        mouse: &MouseData,
+        button: MouseButton,
+        count: usize,
+        _: &FxHashSet<KeyCode>,
+    ) {
+        if button != MouseButton::Left || count != 2 {
+            return;
+        }
+        self.current_dir_field.on_mouse_double_click(mouse.xy);
+        self.name_field.on_mouse_double_click(mouse.xy);
+        if let Some(result) = self.dir_panel.on_mouse_double_click(mouse.xy) {
+            if !result.is_file {
+                self.dir_panel.set_dir(&result.path);
+                self.current_dir_field.set_content(&result.path);
+            }
+        }
+    }
+
     fn on_scroll(&mut self, mouse: &MouseData, _: isize, y_diff: isize, _: &FxHashSet<KeyCode>) {
         self.dir_panel.on_scroll(mouse.xy, y_diff);
     }
@@ -262,7 +286,7 @@ where
         _: &MouseData,
         _: &FxHashSet<KeyCode>,
         _: &GameController,
-        _: &Window,
+        _: Option<&Window>,
     ) -> SceneUpdateResult<SR, SN> {
         self.update(timing)
     }
@@ -273,7 +297,7 @@ where
         timing: &Timing,
         _: &MouseData,
         _: &FxHashSet<KeyCode>,
-        _: &Window,
+        _: Option<&Window>,
     ) -> SceneUpdateResult<SR, SN> {
         self.update(timing)
     }
@@ -0,0 +1,409 @@
+use crate::capture::{argb_image, deliver_screenshot};
+#[cfg(feature = "images")]
+use crate::capture::{stop_recording, update_recording, Recorder};
+use crate::integration::input::{send_input, InputState};
+use crate::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// An [InputEvent] and when it should be delivered
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptedInput {
+    /// Time since the runner started
+    pub at: Duration,
+    pub event: InputEvent,
+}
+
+impl ScriptedInput {
+    pub fn new(at: Duration, event: InputEvent) -> Self {
+        Self { at, event }
+    }
+}
+
+/// Runs a [System] without a window, rendering into an in memory buffer
+///
+/// Time is simulated, each [step][HeadlessRunner::step] advances the clock by exactly
+/// one fixed time step (based on [Options::ups]) so runs are deterministic.
+/// [System::update] will be called with `None` for the window.
+/// Screenshots and recordings are supported, including their hotkeys, but [ScreenshotSource::Window] will be the same as the canvas.
+///
+/// If you're using [Scene][scenes::Scene]s see [headless_scenes][scenes::headless_scenes]
+///
+/// # Usage
+///
+/// ```
+///# use std::time::Duration;
+///# use pixels_graphics_lib::prelude::*;
+///# fn example(system: Box<dyn System>) {
+/// let mut runner = HeadlessRunner::new(240, 160, system, &Options::default())
+///     .with_script(vec![
+///         ScriptedInput::new(Duration::from_millis(100), InputEvent::MouseMove(coord!(10, 10))),
+///         ScriptedInput::new(Duration::from_millis(150), InputEvent::MouseDown(MouseButton::Left)),
+///         ScriptedInput::new(Duration::from_millis(200), InputEvent::MouseUp(MouseButton::Left)),
+///     ]);
+/// runner.run_for(Duration::from_secs(1));
+/// let frame = runner.frame_image();
+///# }
+/// ```
+pub struct HeadlessRunner {
+    width: usize,
+    height: usize,
+    system: Box<dyn System>,
+    timing: Timing,
+    mouse: MouseData,
+    input: InputState,
+    buffer: Vec<u32>,
+    script: VecDeque<ScriptedInput>,
+    elapsed: Duration,
+    exited: bool,
+    screenshot: Option<ScreenshotOptions>,
+    #[cfg(feature = "images")]
+    recording: Option<RecordingOptions>,
+    #[cfg(feature = "images")]
+    recorder: Option<Recorder>,
+}
+
+impl HeadlessRunner {
+    /// # Arguments
+    /// * `width` - Width of the canvas in pixels
+    /// * `height` - Height of the canvas in pixels
+    /// * `system` - Your program
+    /// * `options` - Only [ups][Options::ups], [screenshot][Options::screenshot] and [recording][Options::recording] are used
+    pub fn new(width: usize, height: usize, system: Box<dyn System>, options: &Options) -> Self {
+        Self {
+            width,
+            height,
+            system,
+            timing: Timing::new(options.ups),
+            mouse: MouseData::default(),
+            input: InputState::default(),
+            buffer: Graphics::create_buffer_u32(width, height),
+            script: VecDeque::new(),
+            elapsed: Duration::ZERO,
+            exited: false,
+            screenshot: options.screenshot.clone(),
+            #[cfg(feature = "images")]
+            recording: options.recording.clone(),
+            #[cfg(feature = "images")]
+            recorder: None,
+        }
+    }
+
+    /// Add inputs to be delivered, they don't need to be in order
+    pub fn with_script(mut self, script: Vec<ScriptedInput>) -> Self {
+        for input in script {
+            self.push_input(input);
+        }
+        self
+    }
+
+    /// Add all inputs from `log`, they will be delivered before the same update they were recorded at
+    ///
+    /// [Options::ups] must be the same as [InputLog::ups]
+    pub fn with_input_log(mut self, log: &InputLog) -> Self {
+        let step = Duration::from_secs_f64(self.timing.fixed_time_step);
+        for input in &log.events {
+            self.push_input(ScriptedInput::new(
+                step * input.tick as u32,
+                input.event.clone(),
+            ));
+        }
+        self
+    }
+
+    /// Add an input to be delivered at `input.at`
+    ///
+    /// Inputs with a time that has already passed will be delivered on the next step
+    pub fn push_input(&mut self, input: ScriptedInput) {
+        let idx = self.script.partition_point(|queued| queued.at <= input.at);
+        self.script.insert(idx, input);
+    }
+}
+
+impl HeadlessRunner {
+    /// Deliver any due inputs, then update and render once
+    ///
+    /// # Returns
+    /// false if the system has requested to exit, after which this does nothing
+    pub fn step(&mut self) -> bool {
+        if self.exited {
+            return false;
+        }
+        let mut hotkey_screenshot = false;
+        #[cfg(feature = "images")]
+        let mut hotkey_recording = false;
+        while self.script.front().map(|input| input.at <= self.elapsed) == Some(true) {
+            if let Some(input) = self.script.pop_front() {
+                if let InputEvent::KeyDown(key) = input.event {
+                    hotkey_screenshot |= self.screenshot.as_ref().map(|opts| opts.key) == Some(key);
+                    #[cfg(feature = "images")]
+                    {
+                        hotkey_recording |=
+                            self.recording.as_ref().map(|opts| opts.key) == Some(key);
+                    }
+                }
+                send_input(
+                    self.system.as_mut(),
+                    &mut self.mouse,
+                    &mut self.input,
+                    input.event,
+                );
+            }
+        }
+
+        let step = Duration::from_secs_f64(self.timing.fixed_time_step);
+        self.timing.last = self.timing.now;
+        self.timing.now += step;
+        self.timing.delta = self.timing.fixed_time_step;
+        self.system.update(&self.timing, None);
+        self.timing.updates += 1;
+        //there's no window so only exit is used
+        let exit = self.system.window_commands().contains(&WindowCommand::Exit);
+
+        let mut graphics = Graphics::new_u32_argb(&mut self.buffer, self.width, self.height)
+            .expect("Graphics creation");
+        self.system.render_interpolated(&mut graphics, 0.0);
+        let requested = self
+            .system
+            .screenshot_requested()
+            .map(|source| (source, false));
+        let from_hotkey = if hotkey_screenshot {
+            self.screenshot.as_ref().map(|opts| (opts.source, true))
+        } else {
+            None
+        };
+        for (source, from_hotkey) in requested.into_iter().chain(from_hotkey) {
+            deliver_screenshot(
+                self.system.as_mut(),
+                self.screenshot.as_ref(),
+                source,
+                from_hotkey,
+                graphics.copy_to_image(),
+            );
+        }
+        #[cfg(feature = "images")]
+        {
+            let mut request = self.system.recording_requested();
+            if hotkey_recording {
+                if let Some(opts) = &self.recording {
+                    request = Some(if self.recorder.is_some() {
+                        RecordingRequest::Stop
+                    } else {
+                        RecordingRequest::Start(opts.format, opts.directory.clone())
+                    });
+                }
+            }
+            update_recording(
+                &mut self.recorder,
+                self.system.as_mut(),
+                request,
+                || graphics.copy_to_image(),
+                self.timing.now,
+            );
+        }
+        self.timing.renders += 1;
+        self.timing.update_fps();
+
+        self.elapsed += step;
+
+        if exit || self.system.should_exit() {
+            self.exited = true;
+            #[cfg(feature = "images")]
+            stop_recording(&mut self.recorder, self.system.as_mut(), self.timing.now);
+        }
+        !self.exited
+    }
+
+    /// Step until `duration` of simulated time has passed or the system exits
+    pub fn run_for(&mut self, duration: Duration) {
+        let end = self.elapsed + duration;
+        while self.elapsed < end && self.step() {}
+    }
+
+    /// Step until the system exits or `max` simulated time has passed
+    ///
+    /// # Returns
+    /// true if the system exited
+    pub fn run_until_exit(&mut self, max: Duration) -> bool {
+        self.run_for(max);
+        self.exited
+    }
+}
+
+impl HeadlessRunner {
+    /// Latest rendered frame as ARGB pixels
+    pub fn frame(&self) -> &[u32] {
+        &self.buffer
+    }
+
+    /// Copy of latest rendered frame
+    pub fn frame_image(&self) -> Image {
+        argb_image(&self.buffer, self.width, self.height)
+    }
+
+    pub fn timing(&self) -> &Timing {
+        &self.timing
+    }
+
+    pub fn mouse(&self) -> &MouseData {
+        &self.mouse
+    }
+
+    /// Simulated time since the runner started
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn has_exited(&self) -> bool {
+        self.exited
+    }
+
+    /// Number of inputs waiting to be delivered
+    pub fn pending_inputs(&self) -> usize {
+        self.script.len()
+    }
+
+    pub fn into_system(self) -> Box<dyn System> {
+        self.system
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Default)]
+    struct Log {
+        updates: usize,
+        clicks: Vec<Coord>,
+        keys: Vec<KeyCode>,
+        screenshots: Vec<ScreenshotSource>,
+    }
+
+    struct Recorder {
+        log: Rc<RefCell<Log>>,
+    }
+
+    impl System for Recorder {
+        fn update(&mut self, _: &Timing, window: Option<&Window>) {
+            assert!(window.is_none());
+            self.log.borrow_mut().updates += 1;
+        }
+
+        fn render(&mut self, graphics: &mut Graphics) {
+            graphics.clear(BLUE);
+            for click in &self.log.borrow().clicks {
+                graphics.set_pixel(click.x, click.y, RED);
+            }
+        }
+
+        fn on_mouse_up(&mut self, mouse: &MouseData, _: MouseButton) {
+            self.log.borrow_mut().clicks.push(mouse.xy);
+        }
+
+        fn on_key_down(&mut self, keys: Vec<KeyCode>) {
+            self.log.borrow_mut().keys.extend(keys);
+        }
+
+        fn should_exit(&mut self) -> bool {
+            self.log.borrow().keys.contains(&KeyCode::Escape)
+        }
+
+        fn on_screenshot(&mut self, source: ScreenshotSource, image: Image) {
+            assert_eq!(image.get_pixel(0, 0), BLUE);
+            self.log.borrow_mut().screenshots.push(source);
+        }
+    }
+
+    fn runner(script: Vec<ScriptedInput>) -> (HeadlessRunner, Rc<RefCell<Log>>) {
+        let log = Rc::new(RefCell::new(Log::default()));
+        let system = Box::new(Recorder { log: log.clone() });
+        let options = Options {
+            ups: 10,
+            ..Options::default()
+        };
+        (
+            HeadlessRunner::new(8, 8, system, &options).with_script(script),
+            log,
+        )
+    }
+
+    #[test]
+    fn delivers_inputs_in_order_and_renders() {
+        let (mut runner, log) = runner(vec![
+            ScriptedInput::new(
+                Duration::from_millis(250),
+                InputEvent::MouseUp(MouseButton::Left),
+            ),
+            ScriptedInput::new(
+                Duration::from_millis(100),
+                InputEvent::MouseMove(coord!(2, 3)),
+            ),
+            ScriptedInput::new(
+                Duration::from_millis(200),
+                InputEvent::MouseDown(MouseButton::Left),
+            ),
+        ]);
+
+        runner.run_for(Duration::from_millis(200));
+        assert_eq!(runner.pending_inputs(), 2);
+        assert!(log.borrow().clicks.is_empty());
+
+        runner.run_for(Duration::from_millis(200));
+        assert_eq!(runner.pending_inputs(), 0);
+        assert_eq!(log.borrow().clicks, vec![coord!(2, 3)]);
+        assert_eq!(log.borrow().updates, 4);
+        assert_eq!(runner.timing().updates, 4);
+
+        let image = runner.frame_image();
+        assert_eq!(image.get_pixel(2, 3), RED);
+        assert_eq!(image.get_pixel(0, 0), BLUE);
+    }
+
+    #[test]
+    fn stops_on_exit() {
+        let (mut runner, log) = runner(vec![ScriptedInput::new(
+            Duration::from_millis(300),
+            InputEvent::KeyDown(KeyCode::Escape),
+        )]);
+
+        assert!(runner.run_until_exit(Duration::from_secs(5)));
+        assert_eq!(log.borrow().updates, 4);
+        assert!(!runner.step());
+        assert_eq!(log.borrow().updates, 4);
+    }
+
+    #[test]
+    fn screenshot_hotkey() {
+        let log = Rc::new(RefCell::new(Log::default()));
+        let system = Box::new(Recorder { log: log.clone() });
+        let options = Options {
+            ups: 10,
+            screenshot: Some(ScreenshotOptions::new(
+                KeyCode::F12,
+                ScreenshotSource::Window,
+            )),
+            ..Options::default()
+        };
+        let mut runner = HeadlessRunner::new(8, 8, system, &options).with_script(vec![
+            ScriptedInput::new(
+                Duration::from_millis(100),
+                InputEvent::KeyDown(KeyCode::F11),
+            ),
+            ScriptedInput::new(
+                Duration::from_millis(200),
+                InputEvent::KeyDown(KeyCode::F12),
+            ),
+        ]);
+
+        runner.run_for(Duration::from_millis(200));
+        assert!(log.borrow().screenshots.is_empty());
+        runner.run_for(Duration::from_millis(200));
+        assert_eq!(log.borrow().screenshots, vec![ScreenshotSource::Window]);
+    }
+}
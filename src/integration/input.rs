@@ -0,0 +1,618 @@
+use crate::prelude::*;
+use log::warn;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Input passed to a [System] by a runner
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    /// Mouse cursor moved to canvas coord
+    MouseMove(Coord),
+    /// Mouse button pressed at the current cursor position
+    MouseDown(MouseButton),
+    /// Mouse button released at the current cursor position
+    MouseUp(MouseButton),
+    /// Scroll wheel used, `(x_diff, y_diff)`
+    Scroll(isize, isize),
+    /// Raw mouse movement, `(x_diff, y_diff)`, see [System::on_mouse_motion]
+    MouseMotion(f64, f64),
+    KeyDown(KeyCode),
+    /// Key held long enough to repeat, see [KeyRepeat]
+    KeyRepeat(KeyCode),
+    KeyUp(KeyCode),
+    /// Modifier keys changed, see [MouseData::modifiers]
+    ///
+    /// Scripts and logs don't need this, until the first `Modifiers` event the modifiers are
+    /// worked out from [KeyDown][InputEvent::KeyDown] and [KeyUp][InputEvent::KeyUp] of
+    /// shift, ctrl, alt and logo keys
+    Modifiers(Modifiers),
+    /// Window gained (true) or lost (false) focus
+    Focus(bool),
+    /// Touchscreen touch started, moved or ended
+    Touch(TouchData),
+}
+
+/// Stage of a [TouchData]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TouchState {
+    Started,
+    Moved,
+    Ended,
+    /// The OS stopped tracking the touch, treat this like [Ended][TouchState::Ended]
+    Cancelled,
+}
+
+/// A finger on a touchscreen, see [System::on_touch]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct TouchData {
+    /// Unique while the touch is active, may be reused after it ends
+    pub id: u64,
+    /// Canvas coord
+    pub xy: Coord,
+    pub state: TouchState,
+}
+
+/// An [InputEvent] and the update it happened before
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedInput {
+    /// Value of [Timing::updates] when the event happened
+    pub tick: usize,
+    pub event: InputEvent,
+}
+
+/// Every input passed to a [System] in order, see [InputLogMode]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InputLog {
+    /// [Options::ups] the log was recorded at, replays must use the same value
+    pub ups: usize,
+    pub events: Vec<LoggedInput>,
+}
+
+impl InputLog {
+    pub fn new(ups: usize) -> Self {
+        Self {
+            ups,
+            events: vec![],
+        }
+    }
+}
+
+/// Used to record or replay input, see [Options::input_log]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum InputLogMode {
+    /// Input is passed straight to the [System]
+    #[default]
+    Off,
+    /// All input is recorded and passed to [System::on_input_log] when the program exits
+    Record,
+    /// Input from the window is ignored and the log is passed to the [System] at the same
+    /// [update][Timing::updates] it was recorded at
+    ///
+    /// As long as the [System] only changes in [update][System::update] and input methods
+    /// (and doesn't use random numbers, etc) then the program will behave identically
+    Replay(InputLog),
+}
+
+/// How held keys repeat, see [Options::key_repeat]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum KeyRepeat {
+    /// Use the OS repeat delay and rate
+    #[default]
+    Os,
+    /// Repeat the last pressed key at a fixed rate, ignoring the OS settings
+    Custom {
+        /// How long the key must be held before it starts repeating
+        delay: Duration,
+        /// Time between repeats
+        interval: Duration,
+    },
+    /// Keys never repeat
+    Off,
+}
+
+/// Generates repeats for [KeyRepeat::Custom]
+#[derive(Debug)]
+pub(crate) struct KeyRepeater {
+    delay: Duration,
+    interval: Duration,
+    /// Last pressed key and when it next repeats
+    held: Option<(KeyCode, Instant)>,
+}
+
+impl KeyRepeater {
+    pub fn new(delay: Duration, interval: Duration) -> Self {
+        Self {
+            delay,
+            interval: interval.max(Duration::from_millis(1)),
+            held: None,
+        }
+    }
+
+    /// Modifier keys don't repeat or stop the held key repeating
+    pub fn press(&mut self, key: KeyCode, now: Instant) {
+        if modifier_of(key).is_none() {
+            self.held = Some((key, now + self.delay));
+        }
+    }
+
+    pub fn release(&mut self, key: KeyCode) {
+        if self.held.map(|(held, _)| held) == Some(key) {
+            self.held = None;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.held = None;
+    }
+
+    /// Returns the key if it should repeat by `now`, call until `None` to get every due repeat
+    pub fn due(&mut self, now: Instant) -> Option<KeyCode> {
+        let (key, next) = self.held.as_mut()?;
+        if *next > now {
+            return None;
+        }
+        *next += self.interval;
+        Some(*key)
+    }
+
+    /// When the next repeat is due, if a key is held
+    pub fn next(&self) -> Option<Instant> {
+        self.held.map(|(_, next)| next)
+    }
+}
+
+/// Records, replays or passes through input for a runner
+pub(crate) struct InputHandler {
+    log: Option<InputLog>,
+    replay: Option<VecDeque<LoggedInput>>,
+    state: InputState,
+}
+
+impl InputHandler {
+    pub fn new(options: &Options) -> Self {
+        match &options.input_log {
+            InputLogMode::Off => Self {
+                log: None,
+                replay: None,
+                state: InputState::default(),
+            },
+            InputLogMode::Record => Self {
+                log: Some(InputLog::new(options.ups)),
+                replay: None,
+                state: InputState::default(),
+            },
+            InputLogMode::Replay(log) => {
+                if log.ups != options.ups {
+                    warn!(
+                        "Input log was recorded at {} ups but replaying at {} ups",
+                        log.ups, options.ups
+                    );
+                }
+                Self {
+                    log: None,
+                    replay: Some(log.events.iter().cloned().collect()),
+                    state: InputState::default(),
+                }
+            }
+        }
+    }
+
+    /// Pass input from the window to the system, unless replaying
+    pub fn live(
+        &mut self,
+        system: &mut dyn System,
+        mouse: &mut MouseData,
+        tick: usize,
+        event: InputEvent,
+    ) {
+        if self.replay.is_some() {
+            return;
+        }
+        if let Some(log) = &mut self.log {
+            log.events.push(LoggedInput {
+                tick,
+                event: event.clone(),
+            });
+        }
+        send_input(system, mouse, &mut self.state, event);
+    }
+
+    /// Pass any logged input for `tick` to the system, must be called before each update
+    pub fn replay(&mut self, system: &mut dyn System, mouse: &mut MouseData, tick: usize) {
+        if let Some(replay) = &mut self.replay {
+            while replay.front().map(|input| input.tick <= tick) == Some(true) {
+                if let Some(input) = replay.pop_front() {
+                    send_input(system, mouse, &mut self.state, input.event);
+                }
+            }
+        }
+    }
+
+    /// Pass the recorded log, if any, to the system
+    pub fn finish(&mut self, system: &mut dyn System) {
+        if let Some(log) = self.log.take() {
+            system.on_input_log(log);
+        }
+    }
+}
+
+/// Input state the runner keeps between events that isn't shown to the [System]
+#[derive(Debug, Default)]
+pub(crate) struct InputState {
+    /// ID of the touch acting as the left mouse button
+    primary_touch: Option<u64>,
+    /// If an [InputEvent::Modifiers] has been sent, otherwise modifiers come from key presses
+    os_modifiers: bool,
+    /// Modifier keys held, used until an [InputEvent::Modifiers] is sent
+    modifier_keys: FxHashSet<KeyCode>,
+}
+
+impl InputState {
+    /// Track `key` if it's a modifier and modifiers aren't coming from the OS
+    ///
+    /// # Returns
+    /// The new modifiers if `key` changed them
+    fn modifier_key(&mut self, key: KeyCode, pressed: bool) -> Option<Modifiers> {
+        if self.os_modifiers || modifier_of(key).is_none() {
+            return None;
+        }
+        if pressed {
+            self.modifier_keys.insert(key);
+        } else {
+            self.modifier_keys.remove(&key);
+        }
+        let mut modifiers = Modifiers::default();
+        for key in &self.modifier_keys {
+            match modifier_of(*key) {
+                Some(Modifier::Shift) => modifiers.shift = true,
+                Some(Modifier::Ctrl) => modifiers.ctrl = true,
+                Some(Modifier::Alt) => modifiers.alt = true,
+                Some(Modifier::Logo) => modifiers.logo = true,
+                None => {}
+            }
+        }
+        Some(modifiers)
+    }
+}
+
+enum Modifier {
+    Shift,
+    Ctrl,
+    Alt,
+    Logo,
+}
+
+fn modifier_of(key: KeyCode) -> Option<Modifier> {
+    match key {
+        KeyCode::ShiftLeft | KeyCode::ShiftRight => Some(Modifier::Shift),
+        KeyCode::ControlLeft | KeyCode::ControlRight => Some(Modifier::Ctrl),
+        KeyCode::AltLeft | KeyCode::AltRight => Some(Modifier::Alt),
+        KeyCode::SuperLeft | KeyCode::SuperRight => Some(Modifier::Logo),
+        _ => None,
+    }
+}
+
+/// Update the modifiers and tell `system` if they changed
+fn set_modifiers(system: &mut dyn System, mouse: &mut MouseData, modifiers: Modifiers) {
+    if mouse.modifiers != modifiers {
+        mouse.modifiers = modifiers;
+        system.on_modifiers_changed(modifiers);
+    }
+}
+
+/// Pass `event` to `system` the same way the window runner would
+///
+/// # Arguments
+/// * `state` - Kept by the caller between events
+pub(crate) fn send_input(
+    system: &mut dyn System,
+    mouse: &mut MouseData,
+    state: &mut InputState,
+    event: InputEvent,
+) {
+    match event {
+        InputEvent::MouseMove(xy) => {
+            mouse.xy = xy;
+            system.on_mouse_move(mouse);
+        }
+        InputEvent::MouseDown(button) => {
+            mouse.add_down(mouse.xy, button);
+            system.on_mouse_down(mouse, button);
+        }
+        InputEvent::MouseUp(button) => {
+            mouse.add_up(button);
+            system.on_mouse_up(mouse, button);
+        }
+        InputEvent::Scroll(x_diff, y_diff) => system.on_scroll(mouse, x_diff, y_diff),
+        InputEvent::MouseMotion(x_diff, y_diff) => system.on_mouse_motion(mouse, x_diff, y_diff),
+        InputEvent::KeyDown(key) => {
+            if let Some(modifiers) = state.modifier_key(key, true) {
+                set_modifiers(system, mouse, modifiers);
+            }
+            system.on_key_down(vec![key])
+        }
+        InputEvent::KeyRepeat(key) => system.on_key_repeat(vec![key]),
+        InputEvent::KeyUp(key) => {
+            if let Some(modifiers) = state.modifier_key(key, false) {
+                set_modifiers(system, mouse, modifiers);
+            }
+            system.on_key_up(vec![key])
+        }
+        InputEvent::Modifiers(modifiers) => {
+            state.os_modifiers = true;
+            set_modifiers(system, mouse, modifiers);
+        }
+        InputEvent::Focus(focused) => {
+            //button and key releases aren't sent while unfocused so forget them,
+            //the OS sends the current modifiers when focus returns
+            if !focused {
+                mouse.clear_buttons();
+                state.primary_touch = None;
+                state.modifier_keys.clear();
+                set_modifiers(system, mouse, Modifiers::default());
+            }
+            system.on_focus_changed(focused)
+        }
+        InputEvent::Touch(touch) => {
+            if touch.state == TouchState::Started && state.primary_touch.is_none() {
+                state.primary_touch = Some(touch.id);
+            }
+            if state.primary_touch == Some(touch.id) {
+                send_input(system, mouse, state, InputEvent::MouseMove(touch.xy));
+                match touch.state {
+                    TouchState::Started => send_input(
+                        system,
+                        mouse,
+                        state,
+                        InputEvent::MouseDown(MouseButton::Left),
+                    ),
+                    TouchState::Moved => {}
+                    TouchState::Ended | TouchState::Cancelled => {
+                        state.primary_touch = None;
+                        send_input(system, mouse, state, InputEvent::MouseUp(MouseButton::Left));
+                    }
+                }
+            }
+            system.on_touch(mouse, touch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct KeyLogger {
+        keys: Vec<(usize, KeyCode)>,
+        tick: usize,
+        log: Option<InputLog>,
+    }
+
+    impl System for KeyLogger {
+        fn update(&mut self, _: &Timing, _: Option<&Window>) {
+            self.tick += 1;
+        }
+
+        fn render(&mut self, _: &mut Graphics) {}
+
+        fn on_key_down(&mut self, keys: Vec<KeyCode>) {
+            for key in keys {
+                self.keys.push((self.tick, key));
+            }
+        }
+
+        fn on_input_log(&mut self, log: InputLog) {
+            self.log = Some(log);
+        }
+    }
+
+    fn mouse() -> MouseData {
+        MouseData::default()
+    }
+
+    #[test]
+    fn record_then_replay() {
+        let options = Options {
+            input_log: InputLogMode::Record,
+            ..Options::default()
+        };
+        let mut recording = KeyLogger::default();
+        let mut mouse = mouse();
+        let mut handler = InputHandler::new(&options);
+        for tick in 0..5 {
+            if tick == 1 || tick == 3 {
+                let event = InputEvent::KeyDown(KeyCode::KeyA);
+                handler.live(&mut recording, &mut mouse, tick, event);
+            }
+            handler.replay(&mut recording, &mut mouse, tick);
+            recording.update(&Timing::new(options.ups), None);
+        }
+        handler.finish(&mut recording);
+        let log = recording.log.clone().unwrap();
+        assert_eq!(log.events.len(), 2);
+        assert_eq!(log.events[1].tick, 3);
+
+        let options = Options {
+            input_log: InputLogMode::Replay(log),
+            ..Options::default()
+        };
+        let mut replaying = KeyLogger::default();
+        let mut mouse = self::mouse();
+        let mut handler = InputHandler::new(&options);
+        for tick in 0..5 {
+            //live input must be ignored while replaying
+            handler.live(
+                &mut replaying,
+                &mut mouse,
+                tick,
+                InputEvent::KeyDown(KeyCode::KeyB),
+            );
+            handler.replay(&mut replaying, &mut mouse, tick);
+            replaying.update(&Timing::new(options.ups), None);
+        }
+        handler.finish(&mut replaying);
+        assert_eq!(replaying.keys, recording.keys);
+        assert!(replaying.log.is_none());
+    }
+
+    #[test]
+    fn focus_loss_releases_buttons() {
+        let mut system = KeyLogger::default();
+        let mut mouse = mouse();
+        let mut state = InputState::default();
+        send_input(
+            &mut system,
+            &mut mouse,
+            &mut state,
+            InputEvent::MouseDown(MouseButton::Left),
+        );
+        let shift = Modifiers {
+            shift: true,
+            ..Modifiers::default()
+        };
+        send_input(
+            &mut system,
+            &mut mouse,
+            &mut state,
+            InputEvent::Modifiers(shift),
+        );
+        assert!(mouse.any_held());
+        assert!(mouse.modifiers.shift);
+        send_input(
+            &mut system,
+            &mut mouse,
+            &mut state,
+            InputEvent::Focus(false),
+        );
+        assert!(!mouse.any_held());
+        assert!(!mouse.modifiers.any());
+    }
+
+    #[test]
+    fn repeats_last_key() {
+        let start = Instant::now();
+        let ms = |ms| start + Duration::from_millis(ms);
+        let mut repeater = KeyRepeater::new(Duration::from_millis(300), Duration::from_millis(50));
+        repeater.press(KeyCode::KeyA, start);
+        assert_eq!(repeater.due(ms(299)), None);
+        assert_eq!(repeater.due(ms(300)), Some(KeyCode::KeyA));
+        assert_eq!(repeater.next(), Some(ms(350)));
+        let mut count = 0;
+        while repeater.due(ms(500)).is_some() {
+            count += 1;
+        }
+        //350, 400, 450 and 500
+        assert_eq!(count, 4);
+        repeater.press(KeyCode::KeyB, ms(500));
+        repeater.release(KeyCode::KeyA);
+        assert_eq!(repeater.next(), Some(ms(800)));
+        repeater.press(KeyCode::ShiftLeft, ms(600));
+        assert_eq!(repeater.next(), Some(ms(800)));
+        assert_eq!(repeater.due(ms(800)), Some(KeyCode::KeyB));
+        repeater.release(KeyCode::KeyB);
+        assert_eq!(repeater.next(), None);
+        repeater.press(KeyCode::ControlLeft, ms(900));
+        assert_eq!(repeater.next(), None);
+    }
+
+    #[test]
+    fn modifiers_from_keys_until_os_sends_them() {
+        let mut system = KeyLogger::default();
+        let mut mouse = mouse();
+        let mut state = InputState::default();
+        let mut send =
+            |mouse: &mut MouseData, event| send_input(&mut system, mouse, &mut state, event);
+        send(&mut mouse, InputEvent::KeyDown(KeyCode::ShiftLeft));
+        send(&mut mouse, InputEvent::KeyDown(KeyCode::ShiftRight));
+        send(&mut mouse, InputEvent::KeyUp(KeyCode::ShiftLeft));
+        assert!(mouse.modifiers.shift);
+        send(&mut mouse, InputEvent::KeyUp(KeyCode::ShiftRight));
+        assert!(!mouse.modifiers.any());
+
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Modifiers::default()
+        };
+        send(&mut mouse, InputEvent::Modifiers(ctrl));
+        //the OS is now trusted over key presses
+        send(&mut mouse, InputEvent::KeyDown(KeyCode::AltLeft));
+        assert_eq!(mouse.modifiers, ctrl);
+    }
+
+    #[test]
+    fn first_touch_is_mouse() {
+        let mut system = KeyLogger::default();
+        let mut mouse = mouse();
+        let mut state = InputState::default();
+        let touch = |id, x, state| {
+            InputEvent::Touch(TouchData {
+                id,
+                xy: coord!(x, 0),
+                state,
+            })
+        };
+        send_input(
+            &mut system,
+            &mut mouse,
+            &mut state,
+            touch(4, 10, TouchState::Started),
+        );
+        send_input(
+            &mut system,
+            &mut mouse,
+            &mut state,
+            touch(7, 20, TouchState::Started),
+        );
+        assert_eq!(mouse.is_down(MouseButton::Left), Some(coord!(10, 0)));
+        send_input(
+            &mut system,
+            &mut mouse,
+            &mut state,
+            touch(7, 30, TouchState::Moved),
+        );
+        assert_eq!(mouse.xy, coord!(10, 0));
+        send_input(
+            &mut system,
+            &mut mouse,
+            &mut state,
+            touch(4, 15, TouchState::Ended),
+        );
+        assert_eq!(mouse.xy, coord!(15, 0));
+        assert!(!mouse.any_held());
+    }
+
+    #[test]
+    fn focus_loss_releases_touch() {
+        let mut system = KeyLogger::default();
+        let mut mouse = mouse();
+        let mut state = InputState::default();
+        let touch = |id, x| {
+            InputEvent::Touch(TouchData {
+                id,
+                xy: coord!(x, 0),
+                state: TouchState::Started,
+            })
+        };
+        send_input(&mut system, &mut mouse, &mut state, touch(4, 10));
+        //the OS may never end the touch
+        send_input(
+            &mut system,
+            &mut mouse,
+            &mut state,
+            InputEvent::Focus(false),
+        );
+        assert_eq!(state.primary_touch, None);
+        send_input(&mut system, &mut mouse, &mut state, touch(7, 20));
+        assert_eq!(state.primary_touch, Some(7));
+        assert_eq!(mouse.is_down(MouseButton::Left), Some(coord!(20, 0)));
+    }
+}
@@ -1,2 +1,6 @@
+pub mod headless;
+pub mod input;
+mod overlay;
 pub mod softbuffer_winit;
+mod viewport;
 mod winit_app;
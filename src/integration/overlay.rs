@@ -0,0 +1,106 @@
+use crate::prelude::*;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Number of frames shown in the graph
+const GRAPH_LEN: usize = 60;
+/// Frame time (in seconds) that fills the graph
+const GRAPH_MAX: f64 = 1.0 / 30.0;
+const GRAPH_HEIGHT: usize = 20;
+const PADDING: isize = 2;
+const FONT: PixelFont = PixelFont::Standard4x5;
+const BACKGROUND: Color = Color::new(0, 0, 0, 200);
+const GRAPH_OK: Color = Color::new(80, 220, 80, 255);
+const GRAPH_SLOW: Color = Color::new(230, 70, 70, 255);
+
+/// Stats drawn over the canvas, see [Options::debug_overlay]
+#[derive(Debug)]
+pub(crate) struct DebugOverlay {
+    pub visible: bool,
+    frame_times: VecDeque<f64>,
+    last_render: Option<Instant>,
+    ups: usize,
+    last_update_count: usize,
+    last_update_check: Instant,
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            frame_times: VecDeque::with_capacity(GRAPH_LEN),
+            last_render: None,
+            ups: 0,
+            last_update_count: 0,
+            last_update_check: Instant::now(),
+        }
+    }
+
+    /// Record a render, should be called every frame even if hidden
+    pub fn update(&mut self, timing: &Timing) {
+        if let Some(last) = self.last_render {
+            if self.frame_times.len() == GRAPH_LEN {
+                self.frame_times.pop_front();
+            }
+            self.frame_times
+                .push_back(timing.now.duration_since(last).as_secs_f64());
+        }
+        self.last_render = Some(timing.now);
+        if timing
+            .now
+            .duration_since(self.last_update_check)
+            .as_secs_f32()
+            >= 1.0
+        {
+            self.ups = timing.updates - self.last_update_count;
+            self.last_update_count = timing.updates;
+            self.last_update_check = timing.now;
+        }
+    }
+
+    /// Draw stats in the top left of `graphics`
+    ///
+    /// # Arguments
+    /// * `extra` - Lines to show under the stats, see [System::debug_lines]
+    pub fn render(&self, graphics: &mut Graphics, timing: &Timing, extra: &[String]) {
+        let mut lines = vec![
+            format!("FPS: {}", timing.stats.fps),
+            format!("UPS: {}", self.ups),
+            format!("Updates: {}", timing.updates),
+            format!("Renders: {}", timing.renders),
+        ];
+        lines.extend_from_slice(extra);
+
+        let (char_width, _) = FONT.size();
+        let char_width = char_width + FONT.spacing();
+        let line_height = FONT.line_height() as isize;
+        let text_width = lines.iter().map(|line| line.len()).max().unwrap_or(0) * char_width;
+        let width = text_width.max(GRAPH_LEN) as isize + PADDING * 2;
+        let graph_top = PADDING + line_height * lines.len() as isize;
+        let height = graph_top + GRAPH_HEIGHT as isize + PADDING;
+        graphics.draw_rect(
+            Rect::new_with_size((0, 0), width as usize, height as usize),
+            fill(BACKGROUND),
+        );
+
+        for (i, line) in lines.iter().enumerate() {
+            graphics.draw_text(
+                line,
+                TextPos::px(coord!(PADDING, PADDING + line_height * i as isize)),
+                (WHITE, FONT),
+            );
+        }
+
+        let graph_bottom = graph_top + GRAPH_HEIGHT as isize;
+        for (i, time) in self.frame_times.iter().enumerate() {
+            let bar = ((time / GRAPH_MAX).min(1.0) * GRAPH_HEIGHT as f64).ceil() as isize;
+            let color = if *time > GRAPH_MAX {
+                GRAPH_SLOW
+            } else {
+                GRAPH_OK
+            };
+            let x = PADDING + i as isize;
+            graphics.draw_line((x, graph_bottom - bar), (x, graph_bottom - 1), color);
+        }
+    }
+}
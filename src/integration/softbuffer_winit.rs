@@ -1,13 +1,31 @@
-use crate::integration::winit_app::{make_window, run_app, WinitAppBuilder};
+use crate::capture::{argb_image, deliver_screenshot};
+#[cfg(feature = "images")]
+use crate::capture::{stop_recording, update_recording, Recorder};
+use crate::integration::input::{InputHandler, KeyRepeater};
+use crate::integration::overlay::DebugOverlay;
+use crate::integration::viewport::Viewport;
+use crate::integration::winit_app::{
+    apply_command, initial_scale, make_window, recenter_cursor, run_app, set_fullscreen,
+    toggled_fullscreen, WinitAppBuilder,
+};
 use crate::prelude::*;
+#[cfg(feature = "window_prefs")]
 use log::error;
 use std::num::NonZeroU32;
 use std::ops::Deref;
-use winit::event::{ElementState, Event, KeyEvent, MouseScrollDelta, TouchPhase, WindowEvent};
+use std::time::{Duration, Instant};
+use winit::dpi::PhysicalSize;
+use winit::event::{
+    DeviceEvent, ElementState, Event, KeyEvent, MouseScrollDelta, StartCause, Touch, TouchPhase,
+    WindowEvent,
+};
 use winit::event_loop::ControlFlow;
 use winit::event_loop::EventLoop;
 use winit::keyboard::PhysicalKey;
 
+/// Most seconds of updates that will be run after waking when not using [ControlFlow::Poll]
+const MAX_WAIT_CATCH_UP: f64 = 0.25;
+
 /// Create and run a loop using Softbuffer and Winit
 ///
 /// If you want to use [Scene][scenes::Scene]s consider [run_scenes][scenes::run_scenes]
@@ -15,13 +33,15 @@ use winit::keyboard::PhysicalKey;
 /// # Arguments
 /// * `width` - Width of the whole window canvas in pixels
 /// * `height` - Height of the whole window canvas in pixels
+///   (with [Options::resize_canvas] this is only used for the initial window size)
 /// * `title` - Window title
 /// * `system` - Your program
 /// * `options` - [Options] controls how fast the program can update, [PixelView] styling, etc
 ///
 /// # Returns
 ///
-/// Returns when the program is finished executing either due to it quitting or a fatal error occurring
+/// Returns when the program is finished executing either due to it quitting or a fatal error occurring,
+/// such as the window or softbuffer surface not being created
 pub fn run(
     width: usize,
     height: usize,
@@ -29,39 +49,168 @@ pub fn run(
     system: Box<dyn System>,
     options: Options,
 ) -> Result<(), GraphicsError> {
-    let event_loop = EventLoop::new().expect("Failed to create event loop");
+    let event_loop = EventLoop::new().map_err(GraphicsError::WinitInit)?;
     let title = title.to_string();
+    let mut hotkey_screenshot = false;
+    #[cfg(feature = "images")]
+    let mut hotkey_recording = false;
+    #[cfg(feature = "images")]
+    let mut recorder: Option<Recorder> = None;
+    let mut input = InputHandler::new(&options);
+    let mut next_frame = Instant::now();
+    let mut overlay = DebugOverlay::new();
+    let mut cursor_in_window = false;
+    //the software cursor is drawn by the runner so the system won't know it needs redrawing
+    let mut cursor_moved = false;
+    let mut overlay_toggled = false;
+    let mut repeater = match options.key_repeat {
+        KeyRepeat::Custom { delay, interval } => Some(KeyRepeater::new(delay, interval)),
+        KeyRepeat::Os | KeyRepeat::Off => None,
+    };
     let app = WinitAppBuilder::new(system, options, move |elwt, system, options| {
         elwt.set_control_flow(options.control_flow);
-        let (scale, window) = make_window(elwt, system, options, width, height, title.clone())
-            .expect("Window created");
+        let window = make_window(elwt, system, options, width, height, title.clone())?;
 
-        let context = softbuffer::Context::new(window.clone()).expect("Failed to create context");
-        let mut surface =
-            softbuffer::Surface::new(&context, window.clone()).expect("Failed to create surface");
+        let context = softbuffer::Context::new(window.clone())
+            .map_err(|e| GraphicsError::SoftbufferInit(e.to_string()))?;
+        let mut surface = softbuffer::Surface::new(&context, window.clone())
+            .map_err(|e| GraphicsError::SoftbufferInit(e.to_string()))?;
         let size = window.inner_size();
         if let (Some(win_width), Some(win_height)) =
             (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
         {
             surface
                 .resize(win_width, win_height)
-                .expect("Resized softbuffer");
+                .map_err(|e| GraphicsError::SoftbufferInit(e.to_string()))?;
+        }
+        let viewport = make_viewport(options, &window, size, width, height);
+        if viewport.canvas_size() != (width, height) {
+            let (canvas_width, canvas_height) = viewport.canvas_size();
+            system.on_canvas_resized(canvas_width, canvas_height);
         }
-        (scale, window, surface)
+        let canvas = Graphics::create_buffer_u32(width, height);
+        Ok((viewport, window, surface, canvas, false))
     })
     .setup(move |state, event, elwt, system, timing, mouse, options| {
-        let (scale, window, surface) = state;
+        let (viewport, window, surface, canvas, recenter) = state;
 
         timing.update();
         timing.accumulated_time += timing.delta;
+        if options.control_flow != ControlFlow::Poll {
+            //don't try to catch up on all the updates missed while waiting
+            timing.accumulated_time = timing.accumulated_time.min(MAX_WAIT_CATCH_UP);
+        }
         while timing.accumulated_time >= timing.fixed_time_step {
-            system.update(timing, window.deref());
+            input.replay(system.as_mut(), mouse, timing.updates);
+            if let Some(repeater) = &mut repeater {
+                //repeats are delivered before the update they're due at, like replayed input
+                let behind = timing.accumulated_time - timing.fixed_time_step;
+                let update_time = timing
+                    .now
+                    .checked_sub(Duration::from_secs_f64(behind))
+                    .unwrap_or(timing.now);
+                while let Some(key) = repeater.due(update_time) {
+                    input.live(
+                        system.as_mut(),
+                        mouse,
+                        timing.updates,
+                        InputEvent::KeyRepeat(key),
+                    );
+                }
+            }
+            system.update(timing, Some(window.deref()));
             timing.accumulated_time -= timing.fixed_time_step;
             timing.updates += 1;
         }
 
+        let mut exit = false;
+        if event == Event::AboutToWait {
+            #[allow(deprecated)]
+            let fullscreen = system.fullscreen_requested();
+            let commands = fullscreen.map(WindowCommand::SetFullscreen);
+            for command in commands.into_iter().chain(system.window_commands()) {
+                match apply_command(window, options, command, recenter) {
+                    Ok(exit_requested) => exit |= exit_requested,
+                    Err(e) => system.on_error(&e),
+                }
+            }
+        }
+
+        //hotkeys need a render to capture
+        #[cfg(feature = "images")]
+        let hotkey_pending = hotkey_screenshot || hotkey_recording;
+        #[cfg(not(feature = "images"))]
+        let hotkey_pending = hotkey_screenshot;
+        let runner_redraw = hotkey_pending || overlay_toggled || cursor_moved;
+
         if options.control_flow == ControlFlow::Poll && event == Event::AboutToWait {
-            window.request_redraw();
+            if runner_redraw || overlay.visible || system.needs_redraw() {
+                if options.fps.is_some() && timing.now < next_frame {
+                    elwt.set_control_flow(ControlFlow::WaitUntil(next_frame));
+                } else {
+                    elwt.set_control_flow(ControlFlow::Poll);
+                    window.request_redraw();
+                }
+            } else {
+                //nothing to draw so sleep until the next update instead of spinning
+                let until_update = (timing.fixed_time_step - timing.accumulated_time).max(0.0);
+                elwt.set_control_flow(ControlFlow::WaitUntil(
+                    timing.now + Duration::from_secs_f64(until_update),
+                ));
+            }
+        }
+
+        if options.control_flow != ControlFlow::Poll {
+            match event {
+                Event::NewEvents(StartCause::ResumeTimeReached { .. }) => window.request_redraw(),
+                Event::AboutToWait => {
+                    match system.wakeup_requested() {
+                        Some(Wakeup::Now) => {
+                            elwt.set_control_flow(options.control_flow);
+                            window.request_redraw();
+                        }
+                        Some(Wakeup::At(at)) => elwt.set_control_flow(ControlFlow::WaitUntil(at)),
+                        None => elwt.set_control_flow(options.control_flow),
+                    }
+                    if runner_redraw {
+                        window.request_redraw();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if event == Event::AboutToWait {
+            //wake up in time for the next synthesized repeat
+            if let Some(at) = repeater.as_ref().and_then(KeyRepeater::next) {
+                let sooner = match elwt.control_flow() {
+                    ControlFlow::Poll => false,
+                    ControlFlow::Wait => true,
+                    ControlFlow::WaitUntil(until) => at < until,
+                };
+                if sooner {
+                    elwt.set_control_flow(ControlFlow::WaitUntil(at));
+                }
+            }
+        }
+
+        if let Event::DeviceEvent {
+            event: DeviceEvent::MouseMotion { delta: (x, y) },
+            ..
+        } = event
+        {
+            //device events are sent even when another window is focused
+            if window.has_focus() {
+                if *recenter {
+                    recenter_cursor(window);
+                }
+                input.live(
+                    system.as_mut(),
+                    mouse,
+                    timing.updates,
+                    InputEvent::MouseMotion(x, y),
+                );
+            }
         }
 
         if let Event::WindowEvent { window_id, event } = event {
@@ -71,30 +220,89 @@ pub fn run(
                         if let (Some(win_width), Some(win_height)) =
                             (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
                         {
-                            surface
-                                .resize(win_width, win_height)
-                                .expect("Resized softbuffer");
-
-                            let horz_scale = win_width.get() as usize / width;
-                            let vert_scale = win_height.get() as usize / height;
-                            let new_scale: usize = horz_scale.min(vert_scale);
-                            *scale = new_scale as f64;
+                            if let Err(e) = surface.resize(win_width, win_height) {
+                                system.on_error(&GraphicsError::Presenting(e.to_string()));
+                            } else {
+                                let resized = make_viewport(options, window, size, width, height);
+                                if resized.canvas_size() != viewport.canvas_size() {
+                                    let (canvas_width, canvas_height) = resized.canvas_size();
+                                    system.on_canvas_resized(canvas_width, canvas_height);
+                                }
+                                *viewport = resized;
+                            }
                         }
                     }
-                    WindowEvent::CloseRequested => {
-                        system.on_window_closed();
-                        #[cfg(feature = "window_prefs")]
-                        if let Some(mut prefs) = system.window_prefs() {
-                            prefs.store(window.deref());
-                            //can't return from here so just print out error
-                            let _ = prefs
-                                .save()
-                                .map_err(|err| error!("Unable to save window size/pos: {err:?}"));
-                        }
+                    WindowEvent::CloseRequested if system.on_close_requested() => {
+                        close(
+                            window,
+                            system.as_mut(),
+                            &mut input,
+                            #[cfg(feature = "images")]
+                            &mut recorder,
+                            #[cfg(feature = "images")]
+                            timing.now,
+                        );
                         elwt.exit();
                     }
+                    WindowEvent::Touch(Touch {
+                        id,
+                        location,
+                        phase,
+                        ..
+                    }) => {
+                        let state = match phase {
+                            TouchPhase::Started => TouchState::Started,
+                            TouchPhase::Moved => TouchState::Moved,
+                            TouchPhase::Ended => TouchState::Ended,
+                            TouchPhase::Cancelled => TouchState::Cancelled,
+                        };
+                        let touch = TouchData {
+                            id,
+                            xy: viewport.canvas_coord(location.x, location.y),
+                            state,
+                        };
+                        input.live(
+                            system.as_mut(),
+                            mouse,
+                            timing.updates,
+                            InputEvent::Touch(touch),
+                        );
+                    }
+                    WindowEvent::HoveredFile(path) => system.on_file_hovered(mouse, path),
+                    WindowEvent::HoveredFileCancelled => system.on_file_hover_cancelled(),
+                    WindowEvent::DroppedFile(path) => system.on_file_dropped(mouse, path),
+                    WindowEvent::CursorEntered { .. } => cursor_in_window = true,
+                    WindowEvent::CursorLeft { .. } => {
+                        cursor_in_window = false;
+                        cursor_moved = options.software_cursor.is_some();
+                    }
                     WindowEvent::Occluded(hidden) => system.on_visibility_changed(!hidden),
-                    WindowEvent::Focused(focused) => system.on_focus_changed(focused),
+                    WindowEvent::Focused(focused) => {
+                        if let Some(repeater) = &mut repeater {
+                            repeater.clear();
+                        }
+                        input.live(
+                            system.as_mut(),
+                            mouse,
+                            timing.updates,
+                            InputEvent::Focus(focused),
+                        )
+                    }
+                    WindowEvent::ModifiersChanged(modifiers) => {
+                        let state = modifiers.state();
+                        let modifiers = Modifiers {
+                            shift: state.shift_key(),
+                            ctrl: state.control_key(),
+                            alt: state.alt_key(),
+                            logo: state.super_key(),
+                        };
+                        input.live(
+                            system.as_mut(),
+                            mouse,
+                            timing.updates,
+                            InputEvent::Modifiers(modifiers),
+                        );
+                    }
                     WindowEvent::KeyboardInput {
                         device_id: _device_id,
                         event,
@@ -110,86 +318,236 @@ pub fn run(
                             match state {
                                 ElementState::Pressed => {
                                     if !repeat {
-                                        system.on_key_down(vec![keycode])
+                                        if options.screenshot.as_ref().map(|opts| opts.key)
+                                            == Some(keycode)
+                                        {
+                                            hotkey_screenshot = true;
+                                        }
+                                        #[cfg(feature = "images")]
+                                        if options.recording.as_ref().map(|opts| opts.key)
+                                            == Some(keycode)
+                                        {
+                                            hotkey_recording = true;
+                                        }
+                                        if options.debug_overlay == Some(keycode) {
+                                            overlay.visible = !overlay.visible;
+                                            overlay_toggled = true;
+                                        }
+                                        if options.fullscreen_key
+                                            == Some((keycode, mouse.modifiers))
+                                        {
+                                            set_fullscreen(
+                                                window,
+                                                toggled_fullscreen(window, options),
+                                            );
+                                        }
+                                        if let Some(repeater) = &mut repeater {
+                                            repeater.press(keycode, timing.now);
+                                        }
+                                        input.live(
+                                            system.as_mut(),
+                                            mouse,
+                                            timing.updates,
+                                            InputEvent::KeyDown(keycode),
+                                        )
+                                    } else if options.key_repeat == KeyRepeat::Os {
+                                        input.live(
+                                            system.as_mut(),
+                                            mouse,
+                                            timing.updates,
+                                            InputEvent::KeyRepeat(keycode),
+                                        )
                                     }
                                 }
-                                ElementState::Released => system.on_key_up(vec![keycode]),
+                                ElementState::Released => {
+                                    if let Some(repeater) = &mut repeater {
+                                        repeater.release(keycode);
+                                    }
+                                    input.live(
+                                        system.as_mut(),
+                                        mouse,
+                                        timing.updates,
+                                        InputEvent::KeyUp(keycode),
+                                    )
+                                }
                             }
                         }
                     }
-                    WindowEvent::RedrawRequested => {
-                        let mut buffer = surface.buffer_mut().expect("Accessing softbuffer buffer");
-                        let mut drawing_buffer = Graphics::create_buffer_u32(width, height);
+                    WindowEvent::RedrawRequested => 'frame: {
+                        let mut buffer = match surface.buffer_mut() {
+                            Ok(buffer) => buffer,
+                            Err(e) => {
+                                system.on_error(&GraphicsError::Presenting(e.to_string()));
+                                break 'frame;
+                            }
+                        };
+                        let (width, height) = viewport.canvas_size();
+                        //the canvas is reused between frames, only reallocating if resized
+                        canvas.resize(width * height, 0);
+                        canvas.fill(0);
                         let mut drawing_graphics =
-                            Graphics::new_u32_argb(&mut drawing_buffer, width, height)
-                                .expect("Graphics creation");
-                        system.render(&mut drawing_graphics);
-                        let mut image = drawing_graphics.copy_to_image();
-                        if *scale > 1.0 {
-                            let factor = scale.trunc() as usize;
-                            image = image.scale(
-                                Scaling::nearest_neighbour(factor, factor)
-                                    .expect("Invalid scaling"),
+                            match Graphics::new_u32_argb(canvas, width, height) {
+                                Ok(graphics) => graphics,
+                                Err(e) => {
+                                    system.on_error(&GraphicsError::Presenting(e.to_string()));
+                                    break 'frame;
+                                }
+                            };
+                        let alpha =
+                            (timing.accumulated_time / timing.fixed_time_step).clamp(0.0, 1.0);
+                        system.render_interpolated(&mut drawing_graphics, alpha);
+                        let requested = system.screenshot_requested().map(|source| (source, false));
+                        let from_hotkey = if hotkey_screenshot {
+                            hotkey_screenshot = false;
+                            options.screenshot.as_ref().map(|opts| (opts.source, true))
+                        } else {
+                            None
+                        };
+                        let buffer_width = buffer.width().get() as usize;
+                        let buffer_height = buffer.height().get() as usize;
+                        let border = border_color(options, window).map(|color| color.to_argb());
+                        //captures are taken before the debug overlay and software cursor are drawn
+                        for (source, from_hotkey) in requested.into_iter().chain(from_hotkey) {
+                            let shot = match source {
+                                ScreenshotSource::Canvas => argb_image(canvas, width, height),
+                                ScreenshotSource::Window => {
+                                    if let Some(color) = border {
+                                        viewport.fill_border(
+                                            &mut buffer,
+                                            buffer_width,
+                                            buffer_height,
+                                            color,
+                                        );
+                                    }
+                                    viewport.blit(canvas, &mut buffer, buffer_width, buffer_height);
+                                    argb_image(&buffer, buffer_width, buffer_height)
+                                }
+                            };
+                            deliver_screenshot(
+                                system.as_mut(),
+                                options.screenshot.as_ref(),
+                                source,
+                                from_hotkey,
+                                shot,
                             );
                         }
-                        let physical_size = window.inner_size();
-                        let mut graphics = Graphics::new_u32_argb(
-                            &mut buffer,
-                            physical_size.width as usize,
-                            physical_size.height as usize,
-                        )
-                        .expect("Graphics creation");
-                        graphics.draw_image((0, 0), &image);
+                        #[cfg(feature = "images")]
+                        {
+                            let mut request = system.recording_requested();
+                            if hotkey_recording {
+                                hotkey_recording = false;
+                                if let Some(opts) = &options.recording {
+                                    request = Some(if recorder.is_some() {
+                                        RecordingRequest::Stop
+                                    } else {
+                                        RecordingRequest::Start(opts.format, opts.directory.clone())
+                                    });
+                                }
+                            }
+                            update_recording(
+                                &mut recorder,
+                                system.as_mut(),
+                                request,
+                                || argb_image(canvas, width, height),
+                                timing.now,
+                            );
+                        }
+                        if let Ok(mut graphics) = Graphics::new_u32_argb(canvas, width, height) {
+                            if options.debug_overlay.is_some() {
+                                overlay.update(timing);
+                                if overlay.visible {
+                                    overlay.render(&mut graphics, timing, &system.debug_lines());
+                                }
+                            }
+                            if let (Some(cursors), Some(kind), true) = (
+                                &options.software_cursor,
+                                system.software_cursor(),
+                                cursor_in_window,
+                            ) {
+                                cursors.render(&mut graphics, kind, mouse.xy);
+                            }
+                        }
+                        cursor_moved = false;
+                        overlay_toggled = false;
+                        if let Some(color) = border {
+                            viewport.fill_border(&mut buffer, buffer_width, buffer_height, color);
+                        }
+                        viewport.blit(canvas, &mut buffer, buffer_width, buffer_height);
                         timing.renders += 1;
-                        buffer.present().expect("Softbuffer presented to screen");
+                        if let Some(fps) = options.fps {
+                            let interval = Duration::from_secs_f64(1.0 / fps.max(1) as f64);
+                            next_frame += interval;
+                            //if behind don't try to catch up
+                            if next_frame < timing.now {
+                                next_frame = timing.now + interval;
+                            }
+                        }
+                        if let Err(e) = buffer.present() {
+                            system.on_error(&GraphicsError::Presenting(e.to_string()));
+                        }
                     }
                     WindowEvent::MouseWheel {
                         device_id: _device_id,
                         delta,
-                        phase,
+                        phase: TouchPhase::Moved,
                     } => {
-                        if phase == TouchPhase::Moved {
-                            match delta {
-                                MouseScrollDelta::LineDelta(x, y) => {
-                                    system.on_scroll(mouse, x.round() as isize, y.round() as isize);
-                                }
-                                MouseScrollDelta::PixelDelta(pos) => {
-                                    system.on_scroll(
-                                        mouse,
-                                        pos.x.round() as isize,
-                                        pos.y.round() as isize,
-                                    );
-                                }
+                        let (x_diff, y_diff) = match delta {
+                            MouseScrollDelta::LineDelta(x, y) => {
+                                (x.round() as isize, y.round() as isize)
                             }
-                        }
+                            MouseScrollDelta::PixelDelta(pos) => {
+                                (pos.x.round() as isize, pos.y.round() as isize)
+                            }
+                        };
+                        input.live(
+                            system.as_mut(),
+                            mouse,
+                            timing.updates,
+                            InputEvent::Scroll(x_diff, y_diff),
+                        );
                     }
                     WindowEvent::MouseInput {
                         device_id: _device_id,
                         state,
                         button,
-                    } => match state {
-                        ElementState::Pressed => {
-                            mouse.add_down(mouse.xy, button);
-                            system.on_mouse_down(mouse, button);
-                        }
-                        ElementState::Released => {
-                            mouse.add_up(button);
-                            system.on_mouse_up(mouse, button);
-                        }
-                    },
+                    } => {
+                        let event = match state {
+                            ElementState::Pressed => InputEvent::MouseDown(button),
+                            ElementState::Released => InputEvent::MouseUp(button),
+                        };
+                        input.live(system.as_mut(), mouse, timing.updates, event);
+                    }
+                    //while faking a locked cursor these are just from recentering it
+                    WindowEvent::CursorMoved { .. } if *recenter => {}
                     WindowEvent::CursorMoved {
                         device_id: _device_id,
                         position,
                     } => {
-                        mouse.xy = coord!(position.x, position.y) / *scale;
-                        system.on_mouse_move(mouse);
+                        cursor_in_window = true;
+                        cursor_moved = options.software_cursor.is_some();
+                        let xy = viewport.canvas_coord(position.x, position.y);
+                        input.live(
+                            system.as_mut(),
+                            mouse,
+                            timing.updates,
+                            InputEvent::MouseMove(xy),
+                        );
                     }
                     _ => {}
                 }
             }
         }
 
-        if system.should_exit() {
+        if exit || system.should_exit() {
+            close(
+                window,
+                system.as_mut(),
+                &mut input,
+                #[cfg(feature = "images")]
+                &mut recorder,
+                #[cfg(feature = "images")]
+                timing.now,
+            );
             elwt.exit();
         }
 
@@ -198,6 +556,62 @@ pub fn run(
         timing.last = timing.now;
     });
 
-    run_app(event_loop, app).map_err(GraphicsError::WinitInit)?;
-    Ok(())
+    run_app(event_loop, app)
+}
+
+fn make_viewport(
+    options: &Options,
+    window: &Window,
+    window_size: PhysicalSize<u32>,
+    width: usize,
+    height: usize,
+) -> Viewport {
+    let window_size = (window_size.width as usize, window_size.height as usize);
+    let centered = border_color(options, window).is_some();
+    if options.resize_canvas {
+        let scale = initial_scale(options.scaling, window.scale_factor())
+            .round()
+            .max(1.0) as usize;
+        Viewport::new(
+            WindowScaling::Exact(scale),
+            centered,
+            window_size,
+            (window_size.0 / scale).max(1),
+            (window_size.1 / scale).max(1),
+        )
+    } else {
+        Viewport::new(options.scaling, centered, window_size, width, height)
+    }
+}
+
+/// Stop recording, pass on the input log and store window prefs before exiting
+#[allow(unused_variables)]
+fn close(
+    window: &Window,
+    system: &mut dyn System,
+    input: &mut InputHandler,
+    #[cfg(feature = "images")] recorder: &mut Option<Recorder>,
+    #[cfg(feature = "images")] now: Instant,
+) {
+    #[cfg(feature = "images")]
+    stop_recording(recorder, system, now);
+    input.finish(system);
+    system.on_window_closed();
+    #[cfg(feature = "window_prefs")]
+    if let Some(mut prefs) = system.window_prefs() {
+        if window.fullscreen().is_none() {
+            prefs.store(window);
+        }
+        //can't return from here so just print out error
+        let _ = prefs
+            .save()
+            .map_err(|err| error!("Unable to save window size/pos: {err:?}"));
+    }
+}
+
+/// Color to fill around the canvas with, if it should be centered
+fn border_color(options: &Options, window: &Window) -> Option<Color> {
+    options
+        .letterbox
+        .or_else(|| window.fullscreen().map(|_| BLACK))
 }
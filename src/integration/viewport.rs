@@ -0,0 +1,228 @@
+use crate::prelude::*;
+
+/// Where, and how big, the canvas is drawn in the window
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) struct Viewport {
+    /// Left of the canvas in the window
+    pub x: isize,
+    /// Top of the canvas in the window
+    pub y: isize,
+    /// Width of the canvas in the window
+    pub width: usize,
+    /// Height of the canvas in the window
+    pub height: usize,
+    canvas_width: usize,
+    canvas_height: usize,
+    /// Canvas column for each column of the viewport
+    columns: Vec<usize>,
+}
+
+impl Viewport {
+    /// # Arguments
+    /// * `scaling` - How the canvas should be scaled
+    /// * `centered` - If the canvas should be centered, otherwise it's in the top left
+    /// * `window_size` - Physical size of the window
+    /// * `canvas_width` - Width of the canvas in pixels
+    /// * `canvas_height` - Height of the canvas in pixels
+    pub fn new(
+        scaling: WindowScaling,
+        centered: bool,
+        window_size: (usize, usize),
+        canvas_width: usize,
+        canvas_height: usize,
+    ) -> Self {
+        let (window_width, window_height) = window_size;
+        let canvas_width = canvas_width.max(1);
+        let canvas_height = canvas_height.max(1);
+        let fit_integer = || {
+            (window_width / canvas_width)
+                .min(window_height / canvas_height)
+                .max(1)
+        };
+        let (width, height) = match scaling {
+            WindowScaling::Native
+            | WindowScaling::Double
+            | WindowScaling::Quad
+            | WindowScaling::FitInteger => {
+                (canvas_width * fit_integer(), canvas_height * fit_integer())
+            }
+            WindowScaling::Exact(factor) => {
+                (canvas_width * factor.max(1), canvas_height * factor.max(1))
+            }
+            WindowScaling::FitAspect => {
+                let scale = (window_width as f64 / canvas_width as f64)
+                    .min(window_height as f64 / canvas_height as f64);
+                (
+                    ((canvas_width as f64 * scale) as usize).max(1),
+                    ((canvas_height as f64 * scale) as usize).max(1),
+                )
+            }
+            WindowScaling::Stretch => (window_width.max(1), window_height.max(1)),
+        };
+        let (x, y) = if centered {
+            (
+                ((window_width as isize - width as isize) / 2).max(0),
+                ((window_height as isize - height as isize) / 2).max(0),
+            )
+        } else {
+            (0, 0)
+        };
+        let columns = (0..width).map(|x| x * canvas_width / width).collect();
+        Self {
+            x,
+            y,
+            width,
+            height,
+            canvas_width,
+            canvas_height,
+            columns,
+        }
+    }
+
+    /// Size of the canvas in pixels
+    pub fn canvas_size(&self) -> (usize, usize) {
+        (self.canvas_width, self.canvas_height)
+    }
+
+    /// Convert a window position to a canvas coord
+    ///
+    /// The result will be outside the canvas if the position is outside of the viewport
+    pub fn canvas_coord(&self, x: f64, y: f64) -> Coord {
+        coord!(
+            ((x - self.x as f64) * self.canvas_width as f64 / self.width as f64).floor(),
+            ((y - self.y as f64) * self.canvas_height as f64 / self.height as f64).floor()
+        )
+    }
+
+    /// Draw `canvas` scaled into `dest`
+    ///
+    /// Both buffers must be in the same format, pixels are copied as is
+    ///
+    /// Each canvas row is only scaled once, repeated rows are copied from the first
+    pub fn blit(&self, canvas: &[u32], dest: &mut [u32], dest_width: usize, dest_height: usize) {
+        let cols = self.visible(self.x, self.width, dest_width);
+        let columns = &self.columns[(cols.start as isize - self.x) as usize..];
+        let mut previous: Option<(usize, usize)> = None;
+        for dest_y in self.visible(self.y, self.height, dest_height) {
+            let src_y = (dest_y as isize - self.y) as usize * self.canvas_height / self.height;
+            let start = dest_y * dest_width;
+            match previous {
+                Some((prev_src_y, prev_start)) if prev_src_y == src_y => {
+                    dest.copy_within(
+                        prev_start + cols.start..prev_start + cols.end,
+                        start + cols.start,
+                    );
+                }
+                _ => {
+                    let src_row =
+                        &canvas[src_y * self.canvas_width..(src_y + 1) * self.canvas_width];
+                    let dest_row = &mut dest[start + cols.start..start + cols.end];
+                    for (pixel, src_x) in dest_row.iter_mut().zip(columns) {
+                        *pixel = src_row[*src_x];
+                    }
+                    previous = Some((src_y, start));
+                }
+            }
+        }
+    }
+
+    /// Fill everything in `dest` outside of the viewport with `color`
+    pub fn fill_border(&self, dest: &mut [u32], dest_width: usize, dest_height: usize, color: u32) {
+        let rows = self.visible(self.y, self.height, dest_height);
+        let cols = self.visible(self.x, self.width, dest_width);
+        for (y, row) in dest
+            .chunks_exact_mut(dest_width)
+            .take(dest_height)
+            .enumerate()
+        {
+            if rows.contains(&y) {
+                row[..cols.start].fill(color);
+                row[cols.end..].fill(color);
+            } else {
+                row.fill(color);
+            }
+        }
+    }
+
+    /// Range of destination pixels covered by the viewport on one axis
+    fn visible(&self, start: isize, len: usize, dest_len: usize) -> std::ops::Range<usize> {
+        let end = (start + len as isize).clamp(0, dest_len as isize) as usize;
+        (start.clamp(0, dest_len as isize) as usize)..end
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn integer_modes() {
+        let viewport = Viewport::new(WindowScaling::FitInteger, false, (350, 210), 100, 50);
+        assert_eq!(
+            (viewport.x, viewport.y, viewport.width, viewport.height),
+            (0, 0, 300, 150)
+        );
+
+        let viewport = Viewport::new(WindowScaling::Double, true, (350, 210), 100, 50);
+        assert_eq!(
+            (viewport.x, viewport.y, viewport.width, viewport.height),
+            (25, 30, 300, 150)
+        );
+        assert_eq!(viewport.canvas_coord(25.0, 30.0), coord!(0, 0));
+        assert_eq!(viewport.canvas_coord(27.9, 32.9), coord!(0, 0));
+        assert_eq!(viewport.canvas_coord(28.0, 33.0), coord!(1, 1));
+        assert_eq!(viewport.canvas_coord(10.0, 10.0), coord!(-5, -7));
+
+        let viewport = Viewport::new(WindowScaling::Exact(5), false, (350, 210), 100, 50);
+        assert_eq!((viewport.width, viewport.height), (500, 250));
+    }
+
+    #[test]
+    fn fractional_modes() {
+        let viewport = Viewport::new(WindowScaling::FitAspect, true, (250, 200), 100, 50);
+        assert_eq!(
+            (viewport.x, viewport.y, viewport.width, viewport.height),
+            (0, 37, 250, 125)
+        );
+        assert_eq!(viewport.canvas_coord(249.0, 161.0), coord!(99, 49));
+
+        let viewport = Viewport::new(WindowScaling::Stretch, true, (250, 200), 100, 50);
+        assert_eq!(
+            (viewport.x, viewport.y, viewport.width, viewport.height),
+            (0, 0, 250, 200)
+        );
+        assert_eq!(viewport.canvas_coord(125.0, 100.0), coord!(50, 25));
+    }
+
+    #[test]
+    fn blit_and_border() {
+        let canvas = [1, 2, 3, 4];
+        let mut dest = [0; 6 * 5];
+        let viewport = Viewport::new(WindowScaling::FitInteger, true, (6, 5), 2, 2);
+        viewport.fill_border(&mut dest, 6, 5, 9);
+        viewport.blit(&canvas, &mut dest, 6, 5);
+        #[rustfmt::skip]
+        assert_eq!(dest, [
+            9, 1, 1, 2, 2, 9,
+            9, 1, 1, 2, 2, 9,
+            9, 3, 3, 4, 4, 9,
+            9, 3, 3, 4, 4, 9,
+            9, 9, 9, 9, 9, 9,
+        ]);
+    }
+
+    #[test]
+    fn blit_clipped() {
+        let canvas = [1, 2, 3, 4];
+        let mut dest = [0; 4 * 4];
+        let viewport = Viewport::new(WindowScaling::Exact(3), false, (4, 4), 2, 2);
+        viewport.blit(&canvas, &mut dest, 4, 4);
+        #[rustfmt::skip]
+        assert_eq!(dest, [
+            1, 1, 1, 2,
+            1, 1, 1, 2,
+            1, 1, 1, 2,
+            3, 3, 3, 4,
+        ]);
+    }
+}
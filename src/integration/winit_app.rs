@@ -1,18 +1,21 @@
+use crate::capture::rgba_bytes;
 use crate::prelude::winit;
 #[cfg(feature = "window_prefs")]
 use crate::GraphicsError::LoadingWindowPref;
-use crate::{GraphicsError, MouseData, Options, System, WindowScaling};
+use crate::{
+    CursorGrab, FullscreenMode, GraphicsError, MouseData, Options, System, WindowCommand,
+    WindowScaling,
+};
 use log::error;
 use simple_game_utils::prelude::Timing;
 use std::marker::PhantomData;
 use std::rc::Rc;
 use winit::application::ApplicationHandler;
 use winit::dpi::LogicalSize;
-use winit::dpi::PhysicalSize;
-use winit::error::EventLoopError;
-use winit::event::{Event, WindowEvent};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{DeviceEvent, DeviceId, Event, StartCause, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, EventLoop};
-use winit::window::{CursorGrabMode, Window, WindowId};
+use winit::window::{CursorGrabMode, Fullscreen, Icon, Window, WindowId, WindowLevel};
 
 pub(crate) fn make_window(
     event_loop: &ActiveEventLoop,
@@ -21,59 +24,213 @@ pub(crate) fn make_window(
     width: usize,
     height: usize,
     title: String,
-) -> Result<(f64, Rc<Window>), GraphicsError> {
-    let mut attr = Window::default_attributes();
-    attr.title = title;
+) -> Result<Rc<Window>, GraphicsError> {
+    let mut attr = Window::default_attributes()
+        .with_title(title)
+        .with_resizable(options.resizable)
+        .with_decorations(options.decorations)
+        .with_maximized(options.maximized);
+    if options.always_on_top {
+        attr = attr.with_window_level(WindowLevel::AlwaysOnTop);
+    }
+    if let Some(image) = &options.icon {
+        match Icon::from_rgba(
+            rgba_bytes(image),
+            image.width() as u32,
+            image.height() as u32,
+        ) {
+            Ok(icon) => attr = attr.with_window_icon(Some(icon)),
+            Err(e) => error!("Unable to use window icon: {e:?}"),
+        }
+    }
 
-    let mut window: Window = event_loop
+    let window: Window = event_loop
         .create_window(attr)
-        .expect("Window created after resuming");
-    let mut factor = match options.scaling {
-        WindowScaling::Native => window.scale_factor(),
-        WindowScaling::Double => window.scale_factor() + 1.0,
-        WindowScaling::Quad => window.scale_factor() + 3.0,
-    };
+        .map_err(|e| GraphicsError::WindowInit(e.to_string()))?;
+    let canvas_size = LogicalSize::new(width as u32, height as u32);
     let px_size: PhysicalSize<u32> =
-        LogicalSize::new(width as u32, height as u32).to_physical(factor);
+        canvas_size.to_physical(initial_scale(options.scaling, window.scale_factor()));
+    //a resizable canvas can shrink below its starting size
+    let min_size = match options.scaling {
+        _ if options.resize_canvas => canvas_size.to_physical(1.0),
+        WindowScaling::FitInteger | WindowScaling::FitAspect | WindowScaling::Stretch => {
+            canvas_size.to_physical(1.0)
+        }
+        _ => px_size,
+    };
 
-    window.set_min_inner_size(Some(px_size));
-    let _ = window.request_inner_size(px_size);
+    window.set_min_inner_size(Some(min_size));
+    if !options.maximized {
+        let _ = window.request_inner_size(px_size);
+    }
+    if let Some(monitor) = options
+        .monitor
+        .and_then(|i| event_loop.available_monitors().nth(i))
+    {
+        let pos = monitor.position();
+        let size = monitor.size();
+        window.set_outer_position(PhysicalPosition::new(
+            pos.x + (size.width as i32 - px_size.width as i32).max(0) / 2,
+            pos.y + (size.height as i32 - px_size.height as i32).max(0) / 2,
+        ));
+    }
     window.set_visible(true);
 
-    if options.confine_cursor {
-        #[cfg(target_os = "macos")]
-        let _ = window.set_cursor_grab(CursorGrabMode::Locked);
-        #[cfg(not(target_os = "macos"))]
-        let _ = window.set_cursor_grab(CursorGrabMode::Confined);
-    }
-    if options.hide_cursor {
-        window.set_cursor_visible(false);
+    let grab = if options.confine_cursor {
+        CursorGrab::Confined
+    } else {
+        CursorGrab::Free
+    };
+    if let Err(e) = set_cursor_grab(&window, options, grab) {
+        system.on_error(&e);
     }
     #[cfg(feature = "window_prefs")]
+    let mut window = window;
+    #[cfg(feature = "window_prefs")]
     if let Some(mut prefs) = system.window_prefs() {
         if let Err(e) = prefs.load().map_err(|e| LoadingWindowPref(e.to_string())) {
             error!("Unable to restore window size/pos: {e:?}");
         }
         prefs.restore(&mut window);
     }
-    if window.inner_size() != px_size {
-        let horz_scale = window.inner_size().width as usize / width;
-        let vert_scale = window.inner_size().height as usize / height;
-        let new_scale: usize = horz_scale.min(vert_scale);
-        factor = new_scale as f64;
+    set_fullscreen(&window, options.fullscreen);
+    Ok(Rc::new(window))
+}
+
+/// Number of window pixels per canvas pixel the window starts with
+pub(crate) fn initial_scale(scaling: WindowScaling, scale_factor: f64) -> f64 {
+    match scaling {
+        WindowScaling::Native => scale_factor,
+        WindowScaling::Double
+        | WindowScaling::FitInteger
+        | WindowScaling::FitAspect
+        | WindowScaling::Stretch => scale_factor + 1.0,
+        WindowScaling::Quad => scale_factor + 3.0,
+        WindowScaling::Exact(factor) => factor.max(1) as f64,
+    }
+}
+
+pub(crate) fn set_fullscreen(window: &Window, mode: FullscreenMode) {
+    let fullscreen = match mode {
+        FullscreenMode::Windowed => None,
+        FullscreenMode::Borderless => Some(Fullscreen::Borderless(None)),
+        FullscreenMode::Exclusive => {
+            let video_mode = window.current_monitor().and_then(|monitor| {
+                monitor
+                    .video_modes()
+                    .filter(|mode| mode.size() == monitor.size())
+                    .max_by_key(|mode| (mode.bit_depth(), mode.refresh_rate_millihertz()))
+            });
+            match video_mode {
+                Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+                None => Some(Fullscreen::Borderless(None)),
+            }
+        }
+    };
+    window.set_fullscreen(fullscreen);
+}
+
+/// Change the cursor grab, [Locked][CursorGrab::Locked] also hides the cursor
+/// and unlocking restores the visibility from `options`
+///
+/// # Returns
+/// true if the OS can't lock the cursor and the runner must keep moving it back to the center of the window,
+/// or an error if neither the requested grab or its fallback worked
+pub(crate) fn set_cursor_grab(
+    window: &Window,
+    options: &Options,
+    grab: CursorGrab,
+) -> Result<bool, GraphicsError> {
+    let mut recenter = false;
+    let result = match grab {
+        CursorGrab::Free => window.set_cursor_grab(CursorGrabMode::None),
+        CursorGrab::Confined => window
+            .set_cursor_grab(CursorGrabMode::Confined)
+            .or_else(|_| window.set_cursor_grab(CursorGrabMode::Locked)),
+        CursorGrab::Locked => window.set_cursor_grab(CursorGrabMode::Locked).or_else(|_| {
+            recenter = true;
+            window.set_cursor_grab(CursorGrabMode::Confined)
+        }),
+    };
+    result.map_err(|e| GraphicsError::GrabbingCursor(format!("{grab:?}: {e}")))?;
+    if grab == CursorGrab::Locked {
+        window.set_cursor_visible(false);
+    } else {
+        window.set_cursor_visible(!options.hide_cursor && options.software_cursor.is_none());
+    }
+    Ok(recenter)
+}
+
+/// Move the cursor to the center of the window, used to fake [CursorGrab::Locked]
+pub(crate) fn recenter_cursor(window: &Window) {
+    let size = window.inner_size();
+    let _ = window.set_cursor_position(PhysicalPosition::new(size.width / 2, size.height / 2));
+}
+
+/// Apply a command from [System::window_commands]
+///
+/// # Arguments
+/// * `recenter` - Set to whether the runner must fake [CursorGrab::Locked], see [set_cursor_grab]
+///
+/// # Returns
+/// true if the program should exit, or an error if the command couldn't be applied
+pub(crate) fn apply_command(
+    window: &Window,
+    options: &Options,
+    command: WindowCommand,
+    recenter: &mut bool,
+) -> Result<bool, GraphicsError> {
+    match command {
+        WindowCommand::Exit => return Ok(true),
+        WindowCommand::SetTitle(title) => window.set_title(&title),
+        WindowCommand::SetCursorVisible(visible) => window.set_cursor_visible(visible),
+        WindowCommand::SetCursorGrab(grab) => {
+            let result = set_cursor_grab(window, options, grab);
+            //without a grab recentering would just stop the cursor leaving the middle of the window
+            *recenter = matches!(result, Ok(true));
+            result?;
+        }
+        WindowCommand::Resize(width, height) => {
+            let _ = window.request_inner_size(PhysicalSize::new(width, height));
+        }
+        WindowCommand::SetFullscreen(mode) => set_fullscreen(window, mode),
+        WindowCommand::ToggleFullscreen => {
+            set_fullscreen(window, toggled_fullscreen(window, options))
+        }
+    }
+    Ok(false)
+}
+
+/// The mode the fullscreen key should switch to
+pub(crate) fn toggled_fullscreen(window: &Window, options: &Options) -> FullscreenMode {
+    if window.fullscreen().is_some() {
+        FullscreenMode::Windowed
+    } else if options.fullscreen == FullscreenMode::Windowed {
+        FullscreenMode::Borderless
+    } else {
+        options.fullscreen
     }
-    Ok((factor, Rc::new(window)))
 }
 
 ///
 /// Taken from https://raw.githubusercontent.com/rust-windowing/softbuffer/refs/heads/master/examples/utils/winit_app.rs
 ///
-#[allow(unused_mut)]
-pub(crate) fn run_app(
+/// # Returns
+/// Any error from the event loop, or that stopped the app
+pub(crate) fn run_app<T, Init, Handler>(
     event_loop: EventLoop<()>,
-    mut app: impl ApplicationHandler<()> + 'static,
-) -> Result<(), EventLoopError> {
-    event_loop.run_app(&mut app)
+    mut app: WinitApp<T, Init, Handler>,
+) -> Result<(), GraphicsError>
+where
+    WinitApp<T, Init, Handler>: ApplicationHandler<()>,
+{
+    event_loop
+        .run_app(&mut app)
+        .map_err(GraphicsError::WinitInit)?;
+    match app.error.take() {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
 }
 
 pub(crate) struct WinitApp<T, Init, Handler> {
@@ -82,6 +239,10 @@ pub(crate) struct WinitApp<T, Init, Handler> {
     event: Handler,
 
     state: Option<T>,
+    /// Fatal error that stopped the app
+    error: Option<GraphicsError>,
+    /// If the app has been suspended at least once
+    suspended: bool,
 
     system: Box<dyn System>,
     options: Options,
@@ -98,7 +259,7 @@ pub(crate) struct WinitAppBuilder<T, Init> {
 
 impl<T, Init> WinitAppBuilder<T, Init>
 where
-    Init: FnMut(&ActiveEventLoop, &mut Box<dyn System>, &Options) -> T,
+    Init: FnMut(&ActiveEventLoop, &mut Box<dyn System>, &Options) -> Result<T, GraphicsError>,
 {
     pub fn new(system: Box<dyn System>, options: Options, init: Init) -> Self {
         Self {
@@ -127,7 +288,7 @@ where
 
 impl<T, Init, Handler> WinitApp<T, Init, Handler>
 where
-    Init: FnMut(&ActiveEventLoop, &mut Box<dyn System>, &Options) -> T,
+    Init: FnMut(&ActiveEventLoop, &mut Box<dyn System>, &Options) -> Result<T, GraphicsError>,
     Handler: FnMut(
         &mut T,
         Event<()>,
@@ -149,19 +310,18 @@ where
             event,
             system,
             timing: Timing::new(options.ups),
-            mouse: MouseData {
-                xy: Default::default(),
-                buttons: Default::default(),
-            },
+            mouse: MouseData::default(),
             options,
             state: None,
+            error: None,
+            suspended: false,
         }
     }
 }
 
 impl<T, Init, Handler> ApplicationHandler for WinitApp<T, Init, Handler>
 where
-    Init: FnMut(&ActiveEventLoop, &mut Box<dyn System>, &Options) -> T,
+    Init: FnMut(&ActiveEventLoop, &mut Box<dyn System>, &Options) -> Result<T, GraphicsError>,
     Handler: FnMut(
         &mut T,
         Event<()>,
@@ -174,7 +334,32 @@ where
 {
     fn resumed(&mut self, el: &ActiveEventLoop) {
         debug_assert!(self.state.is_none());
-        self.state = Some((self.init)(el, &mut self.system, &self.options));
+        match (self.init)(el, &mut self.system, &self.options) {
+            Ok(state) => {
+                self.state = Some(state);
+                if self.suspended {
+                    self.system.on_resume();
+                }
+            }
+            Err(err) => {
+                self.error = Some(err);
+                el.exit();
+            }
+        }
+    }
+
+    fn new_events(&mut self, event_loop: &ActiveEventLoop, cause: StartCause) {
+        if let Some(state) = self.state.as_mut() {
+            (self.event)(
+                state,
+                Event::NewEvents(cause),
+                event_loop,
+                &mut self.system,
+                &mut self.timing,
+                &mut self.mouse,
+                &self.options,
+            );
+        }
     }
 
     fn window_event(
@@ -183,16 +368,36 @@ where
         window_id: WindowId,
         event: WindowEvent,
     ) {
-        let state = self.state.as_mut().unwrap();
-        (self.event)(
-            state,
-            Event::WindowEvent { window_id, event },
-            event_loop,
-            &mut self.system,
-            &mut self.timing,
-            &mut self.mouse,
-            &self.options,
-        );
+        if let Some(state) = self.state.as_mut() {
+            (self.event)(
+                state,
+                Event::WindowEvent { window_id, event },
+                event_loop,
+                &mut self.system,
+                &mut self.timing,
+                &mut self.mouse,
+                &self.options,
+            );
+        }
+    }
+
+    fn device_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        device_id: DeviceId,
+        event: DeviceEvent,
+    ) {
+        if let Some(state) = self.state.as_mut() {
+            (self.event)(
+                state,
+                Event::DeviceEvent { device_id, event },
+                event_loop,
+                &mut self.system,
+                &mut self.timing,
+                &mut self.mouse,
+                &self.options,
+            );
+        }
     }
 
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
@@ -210,8 +415,8 @@ where
     }
 
     fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
-        let state = self.state.take();
-        debug_assert!(state.is_some());
-        drop(state);
+        self.suspended = true;
+        self.system.on_suspend();
+        drop(self.state.take());
     }
 }
@@ -4,7 +4,7 @@
 //!
 //! This boilerplate code is needed to use it:
 //!
-//! ```
+//! ```no_run
 //! # use std::error::Error;
 //! # use pixels_graphics_lib::prelude::*;
 //! # use buffer_graphics_lib::Graphics;
@@ -14,7 +14,7 @@
 //! }
 //!
 //! impl System for Example {
-//!   fn update(&mut self, timing: &Timing, _: &Window) {
+//!   fn update(&mut self, timing: &Timing, _: Option<&Window>) {
 //!
 //!   }
 //!
@@ -30,6 +30,8 @@
 //! }
 //!```
 
+pub mod capture;
+pub mod cursor;
 pub mod dialogs;
 mod integration;
 #[cfg(feature = "scenes")]
@@ -39,16 +41,24 @@ pub mod utilities;
 #[cfg(feature = "window_prefs")]
 pub mod window_prefs;
 
-use crate::prelude::{winit, Coord, ALL_KEYS};
+#[cfg(feature = "images")]
+use crate::capture::{RecordingOptions, RecordingRequest};
+use crate::capture::{ScreenshotOptions, ScreenshotSource};
+use crate::cursor::{CursorKind, SoftwareCursor};
+use crate::integration::input::{InputLog, InputLogMode, KeyRepeat, TouchData};
+use crate::prelude::{winit, Color, Coord, ALL_KEYS};
 use crate::ui::styles::UiStyle;
 #[cfg(feature = "window_prefs")]
 use crate::window_prefs::WindowPreferences;
 pub use buffer_graphics_lib;
+use buffer_graphics_lib::image::Image;
 use buffer_graphics_lib::Graphics;
 use rustc_hash::FxHashMap;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use simple_game_utils::prelude::*;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 use winit::event::MouseButton;
 pub use winit::event_loop::ControlFlow;
@@ -56,17 +66,27 @@ use winit::keyboard::KeyCode;
 use winit::window::Window;
 
 pub mod prelude {
+    pub use crate::capture::*;
+    pub use crate::cursor::*;
     pub use crate::dialogs::*;
+    pub use crate::integration::headless::*;
+    pub use crate::integration::input::*;
     pub use crate::integration::softbuffer_winit::run;
     #[cfg(feature = "scenes")]
     pub use crate::scenes::*;
     pub use crate::utilities::virtual_key_codes::*;
     #[cfg(feature = "window_prefs")]
     pub use crate::window_prefs::*;
+    pub use crate::ClickOptions;
+    pub use crate::CursorGrab;
+    pub use crate::FullscreenMode;
     pub use crate::GraphicsError;
+    pub use crate::Modifiers;
     pub use crate::MouseData;
     pub use crate::Options;
     pub use crate::System;
+    pub use crate::Wakeup;
+    pub use crate::WindowCommand;
     pub use crate::WindowScaling;
     pub use buffer_graphics_lib::prelude::*;
     pub use rustc_hash::FxHashSet;
@@ -93,11 +113,25 @@ pub enum GraphicsError {
     #[cfg(feature = "controller")]
     #[error("Unable to init controller: {0}")]
     ControllerInit(String),
+    #[cfg(feature = "images")]
+    #[error("Saving image: {0}")]
+    SavingImage(String),
+    #[error("Creating softbuffer surface: {0}")]
+    SoftbufferInit(String),
+    #[error("Drawing to window: {0}")]
+    Presenting(String),
+    #[error("Grabbing cursor: {0}")]
+    GrabbingCursor(String),
     #[error("Initialing Winit: {0}")]
     WinitInit(#[source] winit::error::EventLoopError),
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// How the canvas is scaled to the window
+///
+/// For all modes the window will be initially sized based on system DPI, except for [Exact][WindowScaling::Exact].
+/// When the window is resized [Native][WindowScaling::Native], [Double][WindowScaling::Double] and [Quad][WindowScaling::Quad]
+/// behave the same as [FitInteger][WindowScaling::FitInteger]
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum WindowScaling {
     /// Use system DPI
@@ -106,6 +140,110 @@ pub enum WindowScaling {
     Double,
     /// Use system DPI + 3
     Quad,
+    /// Always use this many pixels per canvas pixel, ignoring system DPI and the window size
+    Exact(usize),
+    /// Use the largest whole number scale that fits in the window
+    /// Starts at the same size as [Double][WindowScaling::Double]
+    FitInteger,
+    /// Use the largest scale that fits in the window while keeping the aspect ratio
+    /// Starts at the same size as [Double][WindowScaling::Double]
+    ///
+    /// The scale may not be a whole number so some pixels will be larger than others
+    FitAspect,
+    /// Fill the window, ignoring aspect ratio
+    /// Starts at the same size as [Double][WindowScaling::Double]
+    ///
+    /// The scale may not be a whole number so some pixels will be larger than others
+    Stretch,
+}
+
+/// How the window fills the screen
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum FullscreenMode {
+    /// Normal window
+    #[default]
+    Windowed,
+    /// Borderless window covering the current monitor
+    Borderless,
+    /// Exclusive fullscreen using the current monitor's resolution, if this isn't supported
+    /// then [Borderless][FullscreenMode::Borderless] is used
+    Exclusive,
+}
+
+/// How the mouse cursor is kept in the window
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum CursorGrab {
+    /// The cursor can leave the window
+    #[default]
+    Free,
+    /// The cursor can't leave the window
+    /// (on macOS this is the same as [Locked][CursorGrab::Locked])
+    Confined,
+    /// The cursor is hidden and can't move, use [System::on_mouse_motion] to track the mouse
+    /// (on platforms that don't support this, such as Windows and X11, the runner keeps moving the
+    /// cursor back to the center of the window)
+    ///
+    /// [MouseData::xy] stays where the cursor was locked, switching back to another grab
+    /// shows the cursor again unless [Options::hide_cursor] is set
+    Locked,
+}
+
+/// Changes to the window, see [System::window_commands]
+///
+/// These are ignored in a [HeadlessRunner][prelude::HeadlessRunner], except for [Exit][WindowCommand::Exit]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum WindowCommand {
+    /// Close the window and exit the program
+    Exit,
+    /// Change the window title
+    SetTitle(String),
+    /// Show or hide the OS mouse cursor
+    SetCursorVisible(bool),
+    /// Change how the mouse cursor is kept in the window
+    SetCursorGrab(CursorGrab),
+    /// Resize the inside of the window, in physical pixels
+    Resize(u32, u32),
+    /// Change the fullscreen mode
+    SetFullscreen(FullscreenMode),
+    /// Switch fullscreen the same way as [Options::fullscreen_key]
+    ToggleFullscreen,
+}
+
+/// Thresholds for detecting double clicks and long presses, used by [Scene][scenes::Scene]s
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ClickOptions {
+    /// Max time between clicks for them to count as a double (or triple, etc) click
+    /// Default is 500ms
+    pub multi_click_time: Duration,
+    /// Max distance in canvas pixels between clicks for them to count as a double click,
+    /// and that the mouse can move while held for a long press
+    /// Default is 4
+    pub max_distance: usize,
+    /// How long a button must be held without moving for a long press, None to disable
+    /// Default is 800ms
+    pub long_press_time: Option<Duration>,
+}
+
+impl Default for ClickOptions {
+    fn default() -> Self {
+        Self {
+            multi_click_time: Duration::from_millis(500),
+            max_distance: 4,
+            long_press_time: Some(Duration::from_millis(800)),
+        }
+    }
+}
+
+/// When the runner should next wake up, see [System::wakeup_requested]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Wakeup {
+    /// Update and render straight away
+    Now,
+    /// Update and render at this time, unless an event wakes the runner first
+    At(Instant),
 }
 
 #[allow(unused_variables)]
@@ -118,20 +256,127 @@ pub trait System {
     fn window_prefs(&mut self) -> Option<WindowPreferences> {
         None
     }
-    fn update(&mut self, timing: &Timing, window: &Window);
+    /// `window` will be `None` when running in a [HeadlessRunner][prelude::HeadlessRunner]
+    fn update(&mut self, timing: &Timing, window: Option<&Window>);
     fn render(&mut self, graphics: &mut Graphics);
+    /// Called by the runner instead of [render][System::render]
+    ///
+    /// # Arguments
+    /// * `alpha` - How far between the last update and the next one this render is, from 0.0 to 1.0,
+    ///   use this to smooth movement between updates
+    fn render_interpolated(&mut self, graphics: &mut Graphics, alpha: f64) {
+        self.render(graphics);
+    }
     fn on_mouse_move(&mut self, mouse: &MouseData) {}
     fn on_mouse_down(&mut self, mouse: &MouseData, button: MouseButton) {}
     fn on_mouse_up(&mut self, mouse: &MouseData, button: MouseButton) {}
     fn on_scroll(&mut self, mouse: &MouseData, x_diff: isize, y_diff: isize) {}
+    /// Called when the mouse device moves, even if the cursor can't
+    /// (because it's at the edge of the screen or [locked][CursorGrab::Locked])
+    ///
+    /// The diffs are in device units, not canvas pixels, and have no OS acceleration applied
+    /// so are best for mouse look, etc. Only sent while the window is focused
+    fn on_mouse_motion(&mut self, mouse: &MouseData, x_diff: f64, y_diff: f64) {}
+    /// Called for every touch on a touchscreen
+    ///
+    /// The first touch (until it ends) is also sent as mouse moves and [Left][MouseButton::Left] button presses
+    fn on_touch(&mut self, mouse: &MouseData, touch: TouchData) {}
     fn on_key_down(&mut self, keys: Vec<KeyCode>) {}
+    /// Called while a key is held, after [on_key_down][System::on_key_down], see [Options::key_repeat]
+    fn on_key_repeat(&mut self, keys: Vec<KeyCode>) {}
     fn on_key_up(&mut self, keys: Vec<KeyCode>) {}
+    /// Called when shift, ctrl, alt or logo are pressed or released, the current state is
+    /// also in [MouseData::modifiers]
+    fn on_modifiers_changed(&mut self, modifiers: Modifiers) {}
+    /// Called when a file is dragged over the window, `mouse.xy` is the last known cursor position
+    fn on_file_hovered(&mut self, mouse: &MouseData, path: PathBuf) {}
+    /// Called when a file is dragged out of the window without being dropped
+    fn on_file_hover_cancelled(&mut self) {}
+    /// Called when a file is dropped on the window, `mouse.xy` is the last known cursor position
+    fn on_file_dropped(&mut self, mouse: &MouseData, path: PathBuf) {}
+    /// Called when the user tries to close the window, return false to keep it open
+    /// (for example to ask about unsaved changes)
+    ///
+    /// If this returns true [on_window_closed][System::on_window_closed] is called and the program exits
+    /// To close later use [WindowCommand::Exit], which closes the same way
+    fn on_close_requested(&mut self) -> bool {
+        true
+    }
     fn on_window_closed(&mut self) {}
     fn on_visibility_changed(&mut self, visible: bool) {}
+    /// Called when focus changes, when focus is lost all mouse buttons are released
+    /// (without calling [on_mouse_up][System::on_mouse_up]) and [modifiers][MouseData::modifiers] are cleared
     fn on_focus_changed(&mut self, focused: bool) {}
+    /// Called when the OS suspends the program, the window will be recreated when resumed
+    fn on_suspend(&mut self) {}
+    /// Called when the program is resumed after [on_suspend][System::on_suspend]
+    fn on_resume(&mut self) {}
+    /// Called when drawing to the window fails, the frame is skipped and the program continues
+    ///
+    /// Also called if a [WindowCommand] can't be applied, such as the cursor grab not being supported
+    ///
+    /// Fatal errors (such as the window not being created) are returned from [run][prelude::run] instead
+    fn on_error(&mut self, error: &GraphicsError) {}
+    /// Called when the canvas size changes, only if [Options::resize_canvas] is true
+    fn on_canvas_resized(&mut self, width: usize, height: usize) {}
     fn should_exit(&mut self) -> bool {
         false
     }
+    /// Return false if nothing has changed since the last [render][System::render],
+    /// checked before the runner asks for a redraw
+    ///
+    /// Redraws caused by the OS (such as the window being resized) will always happen, as will
+    /// redraws for the [software cursor][Options::software_cursor] moving or the [debug overlay][Options::debug_overlay]
+    /// While nothing needs redrawing with [ControlFlow::Poll] the runner sleeps until the next update is due
+    /// Return true when using [screenshot_requested][System::screenshot_requested] or
+    /// [recording_requested][System::recording_requested] so they are checked
+    fn needs_redraw(&mut self) -> bool {
+        true
+    }
+    /// Return when the runner should next update and render, checked when the runner is about to wait
+    ///
+    /// Only used when [Options::control_flow] isn't [Poll][ControlFlow::Poll], with `Poll` the runner
+    /// only waits for the next update so this isn't checked (use [needs_redraw][System::needs_redraw] instead)
+    fn wakeup_requested(&mut self) -> Option<Wakeup> {
+        None
+    }
+    /// Return a mode to change the window to, checked with [window_commands][System::window_commands]
+    #[deprecated(note = "return WindowCommand::SetFullscreen from window_commands instead")]
+    fn fullscreen_requested(&mut self) -> Option<FullscreenMode> {
+        None
+    }
+    /// Return any changes to make to the window, checked once all pending input has been
+    /// handled and updates have run
+    fn window_commands(&mut self) -> Vec<WindowCommand> {
+        vec![]
+    }
+    /// Return a source to capture a screenshot after the next render
+    /// The image will be passed to [on_screenshot][System::on_screenshot]
+    fn screenshot_requested(&mut self) -> Option<ScreenshotSource> {
+        None
+    }
+    /// Called with a screenshot requested by [screenshot_requested][System::screenshot_requested]
+    /// or [Options::screenshot]
+    fn on_screenshot(&mut self, source: ScreenshotSource, image: Image) {}
+    /// Return a request to start or stop recording the canvas, checked after every render
+    #[cfg(feature = "images")]
+    fn recording_requested(&mut self) -> Option<RecordingRequest> {
+        None
+    }
+    /// Called when a recording has stopped, with the path of the recording or why it failed
+    #[cfg(feature = "images")]
+    fn on_recording_finished(&mut self, result: Result<PathBuf, GraphicsError>) {}
+    /// Which [software cursor][Options::software_cursor] to draw after the next render,
+    /// return None to not draw one
+    fn software_cursor(&self) -> Option<CursorKind> {
+        Some(CursorKind::Arrow)
+    }
+    /// Extra lines shown in the debug overlay, see [Options::debug_overlay]
+    fn debug_lines(&self) -> Vec<String> {
+        vec![]
+    }
+    /// Called before the program exits with all input if [Options::input_log] is [Record][InputLogMode::Record]
+    fn on_input_log(&mut self, log: InputLog) {}
 }
 
 /// Options for program windows
@@ -140,6 +385,10 @@ pub struct Options {
     /// Target and max number of times [Scene::update] can be called per second
     /// Default is 240
     pub ups: usize,
+    /// Max number of times [System::render] can be called per second when using [ControlFlow::Poll],
+    /// renders are spaced evenly and the runner sleeps between them
+    /// Default is None (limited by vsync)
+    pub fps: Option<usize>,
     /// How the window should be scaled
     /// Default is [Double][WindowScaling::Double]
     pub scaling: WindowScaling,
@@ -147,16 +396,82 @@ pub struct Options {
     /// Default is true
     pub vsync: bool,
     /// If OS mouse cursor should be hidden
-    /// (you'll have to draw your own if this is true, this is often called software cursor in games)
+    /// (you'll have to draw your own if this is true, see [software_cursor][Options::software_cursor])
+    /// This can be changed later with [WindowCommand::SetCursorVisible]
     /// Default is false
     pub hide_cursor: bool,
     /// If the mouse cursor should be locked to within this window while it's in the foreground
+    /// This can be changed later with [WindowCommand::SetCursorGrab]
     /// Default is false
     pub confine_cursor: bool,
     /// Style data for [UiElement]
     pub style: UiStyle,
     /// Control how the program loops, see [Winit ControlFlow](https://docs.rs/winit/latest/winit/event_loop/enum.ControlFlow.html)
     pub control_flow: ControlFlow,
+    /// Hotkey to capture screenshots, see [ScreenshotOptions]
+    /// Default is None
+    pub screenshot: Option<ScreenshotOptions>,
+    /// Hotkey to start and stop recording, see [RecordingOptions]
+    /// Default is None
+    #[cfg(feature = "images")]
+    pub recording: Option<RecordingOptions>,
+    /// Record or replay all input, see [InputLogMode]
+    /// Default is [Off][InputLogMode::Off]
+    pub input_log: InputLogMode,
+    /// If set the canvas will be centered in the window and the space around it filled with this color,
+    /// otherwise the canvas is drawn in the top left
+    /// Default is None
+    pub letterbox: Option<Color>,
+    /// If the window should start fullscreen
+    /// When fullscreen the canvas is always centered, using [BLACK] for the border if [letterbox][Options::letterbox] isn't set
+    /// Default is [Windowed][FullscreenMode::Windowed]
+    pub fullscreen: FullscreenMode,
+    /// Key and the exact modifiers that must be held to toggle fullscreen, such as
+    /// `(KeyCode::F11, Modifiers::default())` or Alt+Enter:
+    /// `(KeyCode::Enter, Modifiers { alt: true, ..Modifiers::default() })`
+    /// This switches between [Windowed][FullscreenMode::Windowed] and [fullscreen][Options::fullscreen]
+    /// (or [Borderless][FullscreenMode::Borderless] if that is `Windowed`)
+    /// The key press is still passed to the [System]
+    /// Default is None
+    pub fullscreen_key: Option<(KeyCode, Modifiers)>,
+    /// If true the canvas will grow and shrink with the window instead of always being the
+    /// size passed to [run], the scale the window starts at is kept so pixels stay the same size
+    /// [System::on_canvas_resized] is called with the new size
+    /// Default is false
+    pub resize_canvas: bool,
+    /// Key that shows and hides an overlay in the top left of the canvas with FPS, UPS, a frame
+    /// time graph and the lines from [System::debug_lines]
+    /// Default is None
+    pub debug_overlay: Option<KeyCode>,
+    /// Window icon, use [Image::from_indexed] for an [IndexedImage][prelude::IndexedImage]
+    /// Default is None
+    pub icon: Option<Image>,
+    /// If the window can be resized by the user
+    /// Default is true
+    pub resizable: bool,
+    /// If the window has a title bar and border
+    /// Default is true
+    pub decorations: bool,
+    /// If the window should be kept above other windows
+    /// Default is false
+    pub always_on_top: bool,
+    /// If the window should start maximized
+    /// Default is false
+    pub maximized: bool,
+    /// Index of the monitor to open the window in the center of, if it exists,
+    /// this is ignored if window preferences are restored
+    /// Default is None (chosen by the OS)
+    pub monitor: Option<usize>,
+    /// Cursor images drawn at the mouse position after every render, this hides the OS cursor
+    /// Pick which one with [System::software_cursor]
+    /// Default is None
+    pub software_cursor: Option<SoftwareCursor>,
+    /// Double click and long press detection for [Scene][scenes::Scene]s, see [ClickOptions]
+    pub clicks: ClickOptions,
+    /// How held keys repeat, see [System::on_key_repeat]
+    /// This is ignored by [HeadlessRunner][prelude::HeadlessRunner]
+    /// Default is [Os][KeyRepeat::Os]
+    pub key_repeat: KeyRepeat,
 }
 
 impl Options {
@@ -177,6 +492,7 @@ impl Options {
             confine_cursor,
             style,
             control_flow,
+            ..Self::default()
         }
     }
 }
@@ -185,21 +501,69 @@ impl Default for Options {
     fn default() -> Self {
         Self {
             ups: 240,
+            fps: None,
             scaling: WindowScaling::Double,
             vsync: true,
             hide_cursor: false,
             confine_cursor: false,
             style: UiStyle::default(),
             control_flow: ControlFlow::Poll,
+            screenshot: None,
+            #[cfg(feature = "images")]
+            recording: None,
+            input_log: InputLogMode::Off,
+            letterbox: None,
+            fullscreen: FullscreenMode::Windowed,
+            fullscreen_key: None,
+            resize_canvas: false,
+            debug_overlay: None,
+            icon: None,
+            resizable: true,
+            decorations: true,
+            always_on_top: false,
+            maximized: false,
+            monitor: None,
+            software_cursor: None,
+            clicks: ClickOptions::default(),
+            key_repeat: KeyRepeat::Os,
         }
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Default)]
 pub struct MouseData {
+    /// Cursor position on the canvas
+    ///
+    /// This may be outside of the canvas (including negative) if the cursor is
+    /// in the window border, such as when [Options::letterbox] is used
     pub xy: Coord,
     buttons: FxHashMap<MouseButton, Coord>,
+    /// Modifier keys currently held, use this instead of checking for both
+    /// [ShiftLeft][KeyCode::ShiftLeft] and [ShiftRight][KeyCode::ShiftRight], etc
+    pub modifiers: Modifiers,
+}
+
+/// Modifier keys held down, see [MouseData::modifiers]
+///
+/// This is tracked by the OS so stays correct if keys are pressed while another window is focused,
+/// in scripts and input logs without [InputEvent::Modifiers][prelude::InputEvent::Modifiers] it's
+/// worked out from key presses instead
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    /// Windows key or Command on macOS
+    pub logo: bool,
+}
+
+impl Modifiers {
+    /// If any modifier is held
+    pub fn any(&self) -> bool {
+        self.shift || self.ctrl || self.alt || self.logo
+    }
 }
 
 impl MouseData {
@@ -219,4 +583,8 @@ impl MouseData {
     pub(crate) fn add_down(&mut self, xy: Coord, button: MouseButton) {
         self.buttons.insert(button, xy);
     }
+
+    pub(crate) fn clear_buttons(&mut self) {
+        self.buttons.clear();
+    }
 }
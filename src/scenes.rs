@@ -1,8 +1,10 @@
 use crate::integration::softbuffer_winit::run;
 use crate::prelude::*;
 use crate::ui::styles::UiStyle;
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::fmt::Debug;
+use std::path::PathBuf;
+use std::time::Instant;
 use winit::event::MouseButton;
 use winit::keyboard::KeyCode;
 use winit::window::Window;
@@ -38,12 +40,46 @@ pub fn run_scenes<
         window_prefs,
         scene_switcher,
         options.style.clone(),
+        options.clicks,
         pre_post,
     )?);
     run(width, height, title, system, options)?;
     Ok(())
 }
 
+/// Create a [HeadlessRunner] for programs built using [Scene]s
+///
+/// This is useful for testing, see [HeadlessRunner] for details
+///
+/// # Arguments
+/// * `width` - Width of the canvas in pixels
+/// * `height` - Height of the canvas in pixels
+/// * `scene_switcher` - [SceneSwitcher] Adds new scenes to the stack
+/// * `init_scene` - The initial [Scene] to use
+/// * `options` - [Options] controls how fast the program updates, [UiElement] styling, etc
+/// * `pre_post` - [PrePost] hooks, use [empty_pre_post] if not needed
+pub fn headless_scenes<
+    SR: Clone + PartialEq + Debug + 'static,
+    SN: Clone + PartialEq + Debug + 'static,
+>(
+    width: usize,
+    height: usize,
+    scene_switcher: SceneSwitcher<SR, SN>,
+    init_scene: Box<dyn Scene<SR, SN>>,
+    options: &Options,
+    pre_post: Box<dyn PrePost<SR, SN>>,
+) -> Result<HeadlessRunner, GraphicsError> {
+    let system = Box::new(SceneHost::new(
+        init_scene,
+        None,
+        scene_switcher,
+        options.style.clone(),
+        options.clicks,
+        pre_post,
+    )?);
+    Ok(HeadlessRunner::new(width, height, system, options))
+}
+
 /// Creates new scenes.
 ///
 /// # Important
@@ -110,6 +146,39 @@ pub trait Scene<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> {
     #[cfg(not(any(feature = "controller", feature = "controller_xinput")))]
     #[allow(unused_variables)]
     fn render(&self, graphics: &mut Graphics, mouse: &MouseData, held_keys: &FxHashSet<KeyCode>) {}
+    /// Render scene contents using `graphics`, called instead of [render][Scene::render]
+    ///
+    /// # Arguments
+    /// * `alpha` - How far between the last update and the next one this render is, from 0.0 to 1.0,
+    ///   use this to smooth movement between updates
+    #[cfg(any(feature = "controller", feature = "controller_xinput"))]
+    #[allow(unused_variables)]
+    fn render_interpolated(
+        &self,
+        graphics: &mut Graphics,
+        mouse: &MouseData,
+        held_keys: &FxHashSet<KeyCode>,
+        controller: &GameController,
+        alpha: f64,
+    ) {
+        self.render(graphics, mouse, held_keys, controller);
+    }
+    /// Render scene contents using `graphics`, called instead of [render][Scene::render]
+    ///
+    /// # Arguments
+    /// * `alpha` - How far between the last update and the next one this render is, from 0.0 to 1.0,
+    ///   use this to smooth movement between updates
+    #[cfg(not(any(feature = "controller", feature = "controller_xinput")))]
+    #[allow(unused_variables)]
+    fn render_interpolated(
+        &self,
+        graphics: &mut Graphics,
+        mouse: &MouseData,
+        held_keys: &FxHashSet<KeyCode>,
+        alpha: f64,
+    ) {
+        self.render(graphics, mouse, held_keys);
+    }
     /// Called when a keyboard key is being pressed down
     ///
     /// # Arguments
@@ -118,6 +187,14 @@ pub trait Scene<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> {
     /// * `held_keys` - Any other keys that are being pressed down
     #[allow(unused_variables)]
     fn on_key_down(&mut self, key: KeyCode, mouse: &MouseData, held_keys: &FxHashSet<KeyCode>) {}
+    /// Called while a keyboard key is held down, see [Options::key_repeat]
+    ///
+    /// # Arguments
+    /// * `key` - The held key
+    /// * `mouse` - position, held state of mouse
+    /// * `held_keys` - Any keys that are being pressed down
+    #[allow(unused_variables)]
+    fn on_key_repeat(&mut self, key: KeyCode, mouse: &MouseData, held_keys: &FxHashSet<KeyCode>) {}
     /// Called when a keyboard key has been released
     ///
     /// # Arguments
@@ -174,6 +251,41 @@ pub trait Scene<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> {
         held_keys: &FxHashSet<KeyCode>,
     ) {
     }
+    /// Called after [on_mouse_click] when the click quickly followed the previous click(s)
+    /// of the same button, see [ClickOptions]
+    ///
+    /// # Arguments
+    /// * `down_at` - position where mouse button was clicked
+    /// * `mouse` - position, held state of mouse
+    /// * `mouse_button` = which button was clicked
+    /// * `count` - Number of clicks in a row, 2 for a double click, 3 for a triple click, etc
+    /// * `held_keys` - Any keyboards keys that are being pressed down
+    #[allow(unused_variables)]
+    fn on_mouse_multi_click(
+        &mut self,
+        down_at: Coord,
+        mouse: &MouseData,
+        mouse_button: MouseButton,
+        count: usize,
+        held_keys: &FxHashSet<KeyCode>,
+    ) {
+    }
+    /// Called when a mouse button has been held down without moving, see [ClickOptions]
+    ///
+    /// [on_mouse_up] and [on_mouse_click] will still be called when it's released
+    ///
+    /// # Arguments
+    /// * `mouse` - position, held state of mouse
+    /// * `mouse_button` = which button is held
+    /// * `held_keys` - Any keyboards keys that are being pressed down
+    #[allow(unused_variables)]
+    fn on_mouse_long_press(
+        &mut self,
+        mouse: &MouseData,
+        mouse_button: MouseButton,
+        held_keys: &FxHashSet<KeyCode>,
+    ) {
+    }
     /// Called when the mouse moved while any button is held down
     ///
     /// # Arguments
@@ -197,6 +309,22 @@ pub trait Scene<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> {
         held_keys: &FxHashSet<KeyCode>,
     ) {
     }
+    /// Called when the mouse device moves, even if the cursor can't, see [System::on_mouse_motion]
+    ///
+    /// # Arguments
+    /// * `mouse` - position, held state of mouse
+    /// * `x_diff` - The raw distance moved horizontally
+    /// * `y_diff` - The raw distance moved vertically
+    /// * `held_keys` - Any keyboards keys that are being pressed down
+    #[allow(unused_variables)]
+    fn on_mouse_motion(
+        &mut self,
+        mouse: &MouseData,
+        x_diff: f64,
+        y_diff: f64,
+        held_keys: &FxHashSet<KeyCode>,
+    ) {
+    }
     /// During this method the scene should update animations and anything else that relies on time
     /// or on held keys
     ///
@@ -204,6 +332,7 @@ pub trait Scene<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> {
     /// * `timing` - Deltas and other timing info, generally you should use the `fixed_time_step` field
     /// * `xy` - The on screen coord of the mouse cursor
     /// * `held_keys` - Any keyboards keys that are being pressed down
+    /// * `window` - The program window, this will be `None` when running in a [HeadlessRunner]
     ///
     /// # Returns
     ///
@@ -218,7 +347,7 @@ pub trait Scene<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> {
         mouse: &MouseData,
         held_keys: &FxHashSet<KeyCode>,
         controller: &GameController,
-        window: &Window,
+        window: Option<&Window>,
     ) -> SceneUpdateResult<SR, SN>;
     /// During this method the scene should update animations and anything else that relies on time
     /// or on held keys
@@ -227,6 +356,7 @@ pub trait Scene<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> {
     /// * `timing` - Deltas and other timing info, generally you should use the `fixed_time_step` field
     /// * `xy` - The on screen coord of the mouse cursor
     /// * `held_keys` - Any keyboards keys that are being pressed down
+    /// * `window` - The program window, this will be `None` when running in a [HeadlessRunner]
     ///
     /// # Returns
     ///
@@ -240,7 +370,7 @@ pub trait Scene<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> {
         timing: &Timing,
         mouse: &MouseData,
         held_keys: &FxHashSet<KeyCode>,
-        window: &Window,
+        window: Option<&Window>,
     ) -> SceneUpdateResult<SR, SN>;
     /// Called when a child scene is closing
     ///
@@ -248,11 +378,107 @@ pub trait Scene<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> {
     /// * `result` - Optional data from child scene
     #[allow(unused_variables)]
     fn resuming(&mut self, result: Option<SR>) {}
+    /// Return false if nothing has changed since the last [render][Scene::render]
+    /// See [System::needs_redraw]
+    fn needs_redraw(&mut self) -> bool {
+        true
+    }
+    /// Return any changes to make to the window if this is the active scene, checked once all
+    /// pending input has been handled and updates have run
+    /// See [WindowCommand]
+    fn window_commands(&mut self) -> Vec<WindowCommand> {
+        vec![]
+    }
+    /// Called for every touch on a touchscreen if this is the active scene
+    ///
+    /// The first touch is also sent as mouse events, see [System::on_touch]
+    ///
+    /// # Arguments
+    /// * `touch` - ID, position and state of the touch
+    /// * `mouse` - position, held state of mouse
+    /// * `held_keys` - Any keys that are being pressed down
+    #[allow(unused_variables)]
+    fn on_touch(&mut self, touch: TouchData, mouse: &MouseData, held_keys: &FxHashSet<KeyCode>) {}
+    /// Called when a file is dragged over the window if this is the active scene
+    ///
+    /// # Arguments
+    /// * `path` - The file being dragged
+    /// * `mouse` - position, held state of mouse, the position is the last known cursor position
+    /// * `held_keys` - Any keys that are being pressed down
+    #[allow(unused_variables)]
+    fn on_file_hovered(
+        &mut self,
+        path: PathBuf,
+        mouse: &MouseData,
+        held_keys: &FxHashSet<KeyCode>,
+    ) {
+    }
+    /// Called when a file is dragged out of the window without being dropped if this is the active scene
+    fn on_file_hover_cancelled(&mut self) {}
+    /// Called when a file is dropped on the window if this is the active scene
+    ///
+    /// # Arguments
+    /// * `path` - The dropped file
+    /// * `mouse` - position, held state of mouse, the position is the last known cursor position
+    /// * `held_keys` - Any keys that are being pressed down
+    #[allow(unused_variables)]
+    fn on_file_dropped(
+        &mut self,
+        path: PathBuf,
+        mouse: &MouseData,
+        held_keys: &FxHashSet<KeyCode>,
+    ) {
+    }
+    /// Called when the OS suspends the program if this is the active scene, see [System::on_suspend]
+    fn on_suspend(&mut self) {}
+    /// Called when the program is resumed if this is the active scene, see [System::on_resume]
+    fn on_resume(&mut self) {}
+    /// Called when the user tries to close the window if this is the active scene,
+    /// return false to keep it open, see [System::on_close_requested]
+    fn on_close_requested(&mut self) -> bool {
+        true
+    }
+    /// Called when drawing to the window fails if this is the active scene, see [System::on_error]
+    #[allow(unused_variables)]
+    fn on_error(&mut self, error: &GraphicsError) {}
+    /// Which [software cursor][Options::software_cursor] to draw if this is the active scene
+    /// See [System::software_cursor]
+    fn software_cursor(&self) -> Option<CursorKind> {
+        Some(CursorKind::Arrow)
+    }
+    /// Return when the runner should next update and render
+    /// See [System::wakeup_requested]
+    fn wakeup_requested(&mut self) -> Option<Wakeup> {
+        None
+    }
     /// Return true if this scene doesn't fill the screen or is transparent
     /// If this returns false the previous fullscreen scene will render as well
     fn is_dialog(&self) -> bool {
         false
     }
+    /// Return a source to capture a screenshot after the next render
+    /// The image will be passed to [on_screenshot][Scene::on_screenshot]
+    fn screenshot_requested(&mut self) -> Option<ScreenshotSource> {
+        None
+    }
+    /// Called with a screenshot requested by [screenshot_requested][Scene::screenshot_requested]
+    /// or [Options::screenshot] if this is the active scene
+    #[allow(unused_variables)]
+    fn on_screenshot(&mut self, source: ScreenshotSource, image: Image) {}
+    /// Return a request to start or stop recording the canvas, checked after every render
+    #[cfg(feature = "images")]
+    fn recording_requested(&mut self) -> Option<RecordingRequest> {
+        None
+    }
+    /// Called when a recording has stopped if this is the active scene, with the path of the recording or why it failed
+    #[cfg(feature = "images")]
+    #[allow(unused_variables)]
+    fn on_recording_finished(&mut self, result: Result<PathBuf, GraphicsError>) {}
+    /// Called when the canvas size changes, only if [Options::resize_canvas] is true
+    ///
+    /// Scenes pushed after the canvas has been resized are called straight away
+    #[allow(unused_variables)]
+    fn on_canvas_resized(&mut self, width: usize, height: usize) {}
 }
 
 pub trait PrePost<SR, SN> {
@@ -297,7 +523,7 @@ pub trait PrePost<SR, SN> {
         mouse: &MouseData,
         held_keys: &FxHashSet<KeyCode>,
         scenes: &mut [Box<dyn Scene<SR, SN>>],
-        window: &Window,
+        window: Option<&Window>,
     );
     #[cfg(not(any(feature = "controller", feature = "controller_xinput")))]
     fn post_update(
@@ -306,7 +532,7 @@ pub trait PrePost<SR, SN> {
         mouse: &MouseData,
         held_keys: &FxHashSet<KeyCode>,
         scenes: &mut [Box<dyn Scene<SR, SN>>],
-        window: &Window,
+        window: Option<&Window>,
     );
     #[cfg(any(feature = "controller", feature = "controller_xinput"))]
     fn pre_update(
@@ -316,7 +542,7 @@ pub trait PrePost<SR, SN> {
         held_keys: &FxHashSet<KeyCode>,
         scenes: &mut [Box<dyn Scene<SR, SN>>],
         controller: &GameController,
-        window: &Window,
+        window: Option<&Window>,
     );
     #[cfg(any(feature = "controller", feature = "controller_xinput"))]
     fn post_update(
@@ -326,8 +552,23 @@ pub trait PrePost<SR, SN> {
         held_keys: &FxHashSet<KeyCode>,
         scenes: &mut [Box<dyn Scene<SR, SN>>],
         controller: &GameController,
-        window: &Window,
+        window: Option<&Window>,
     );
+    /// Called before the program exits with all input if [Options::input_log] is [Record][InputLogMode::Record]
+    #[allow(unused_variables)]
+    fn on_input_log(&mut self, log: InputLog) {}
+    /// Return any changes to make to the window, checked once all pending input has been
+    /// handled and updates have run
+    /// See [WindowCommand]
+    fn window_commands(&mut self) -> Vec<WindowCommand> {
+        vec![]
+    }
+    /// Called when the canvas size changes, only if [Options::resize_canvas] is true
+    #[allow(unused_variables)]
+    fn on_canvas_resized(&mut self, width: usize, height: usize) {}
+    /// Called when drawing to the window fails, see [System::on_error]
+    #[allow(unused_variables)]
+    fn on_error(&mut self, error: &GraphicsError) {}
 }
 #[cfg(any(feature = "controller", feature = "controller_xinput"))]
 pub fn empty_pre_post<SR, SN>() -> Box<dyn PrePost<SR, SN>> {
@@ -360,7 +601,7 @@ pub fn empty_pre_post<SR, SN>() -> Box<dyn PrePost<SR, SN>> {
             _: &FxHashSet<KeyCode>,
             _: &mut [Box<dyn Scene<SR, SN>>],
             _: &GameController,
-            _: &Window,
+            _: Option<&Window>,
         ) {
         }
 
@@ -371,7 +612,7 @@ pub fn empty_pre_post<SR, SN>() -> Box<dyn PrePost<SR, SN>> {
             _: &FxHashSet<KeyCode>,
             _: &mut [Box<dyn Scene<SR, SN>>],
             _: &GameController,
-            _: &Window,
+            _: Option<&Window>,
         ) {
         }
     }
@@ -405,7 +646,7 @@ pub fn empty_pre_post<SR, SN>() -> Box<dyn PrePost<SR, SN>> {
             _: &MouseData,
             _: &FxHashSet<KeyCode>,
             _: &mut [Box<dyn Scene<SR, SN>>],
-            _: &Window,
+            _: Option<&Window>,
         ) {
         }
 
@@ -415,7 +656,7 @@ pub fn empty_pre_post<SR, SN>() -> Box<dyn PrePost<SR, SN>> {
             _: &MouseData,
             _: &FxHashSet<KeyCode>,
             _: &mut [Box<dyn Scene<SR, SN>>],
-            _: &Window,
+            _: Option<&Window>,
         ) {
         }
     }
@@ -433,6 +674,87 @@ struct SceneHost<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> {
     controller: GameController,
     mouse: MouseData,
     pre_post: Box<dyn PrePost<SR, SN>>,
+    canvas_size: Option<(usize, usize)>,
+    /// If the scene stack changed since the last render
+    scenes_changed: bool,
+    clicks: ClickTracker,
+    click_options: ClickOptions,
+    /// Time of the latest update
+    now: Instant,
+    /// Wall clock time of the latest update, used to time input that arrives between updates
+    updated_at: Instant,
+}
+
+/// Tracks mouse presses for multi clicks and long presses
+#[derive(Debug, Default)]
+struct ClickTracker {
+    /// Button, position, time and count of the latest click
+    last_click: Option<(MouseButton, Coord, Instant, usize)>,
+    /// Held buttons that haven't become a long press yet
+    presses: FxHashMap<MouseButton, (Coord, Instant)>,
+}
+
+impl ClickTracker {
+    fn down(&mut self, button: MouseButton, xy: Coord, now: Instant) {
+        self.presses.insert(button, (xy, now));
+    }
+
+    fn moved(&mut self, xy: Coord, options: &ClickOptions) {
+        self.presses
+            .retain(|_, (down, _)| down.distance(xy) <= options.max_distance);
+    }
+
+    /// # Returns
+    /// The number of clicks in a row
+    fn up(
+        &mut self,
+        button: MouseButton,
+        xy: Coord,
+        now: Instant,
+        options: &ClickOptions,
+    ) -> usize {
+        self.presses.remove(&button);
+        let count = match self.last_click {
+            Some((last_button, last_xy, at, count))
+                if last_button == button
+                    && now.duration_since(at) <= options.multi_click_time
+                    && last_xy.distance(xy) <= options.max_distance =>
+            {
+                count + 1
+            }
+            _ => 1,
+        };
+        self.last_click = Some((button, xy, now, count));
+        count
+    }
+
+    /// Removes and returns any buttons that have been held long enough
+    fn long_presses(&mut self, now: Instant, options: &ClickOptions) -> Vec<MouseButton> {
+        let Some(time) = options.long_press_time else {
+            return vec![];
+        };
+        let buttons: Vec<MouseButton> = self
+            .presses
+            .iter()
+            .filter(|(_, (_, at))| now.duration_since(*at) >= time)
+            .map(|(button, _)| *button)
+            .collect();
+        for button in &buttons {
+            self.presses.remove(button);
+        }
+        buttons
+    }
+
+    /// When the earliest held button will become a long press
+    fn next_long_press(&self, options: &ClickOptions) -> Option<Instant> {
+        let time = options.long_press_time?;
+        self.presses.values().map(|(_, at)| *at + time).min()
+    }
+
+    fn clear(&mut self) {
+        self.presses.clear();
+        self.last_click = None;
+    }
 }
 
 impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> SceneHost<SR, SN> {
@@ -441,6 +763,7 @@ impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> SceneHost<SR,
         window_prefs: Option<WindowPreferences>,
         scene_switcher: SceneSwitcher<SR, SN>,
         style: UiStyle,
+        click_options: ClickOptions,
         pre_post: Box<dyn PrePost<SR, SN>>,
     ) -> Result<Self, GraphicsError> {
         Ok(Self {
@@ -451,10 +774,13 @@ impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> SceneHost<SR,
             window_prefs,
             scene_switcher,
             style,
-            mouse: MouseData {
-                xy: Default::default(),
-                buttons: Default::default(),
-            },
+            mouse: MouseData::default(),
+            canvas_size: None,
+            scenes_changed: false,
+            clicks: ClickTracker::default(),
+            click_options,
+            now: Instant::now(),
+            updated_at: Instant::now(),
             #[cfg(any(feature = "controller", feature = "controller_xinput"))]
             controller: GameController::new()
                 .map_err(|e| GraphicsError::ControllerInit(e.to_string()))?,
@@ -462,12 +788,28 @@ impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> SceneHost<SR,
     }
 }
 
+impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> SceneHost<SR, SN> {
+    /// Time of input arriving now, on the same clock as [Timing::now]
+    ///
+    /// The runner may wait a long time between updates so the time since the latest update is added
+    fn event_time(&self) -> Instant {
+        self.now + self.updated_at.elapsed()
+    }
+}
+
 impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> System for SceneHost<SR, SN> {
     fn window_prefs(&mut self) -> Option<WindowPreferences> {
         self.window_prefs.clone()
     }
 
-    fn update(&mut self, timing: &Timing, window: &Window) {
+    fn update(&mut self, timing: &Timing, window: Option<&Window>) {
+        self.now = timing.now;
+        self.updated_at = Instant::now();
+        for button in self.clicks.long_presses(self.now, &self.click_options) {
+            if let Some(active) = self.scenes.last_mut() {
+                active.on_mouse_long_press(&self.mouse, button, &self.held_keys);
+            }
+        }
         #[cfg(any(feature = "controller", feature = "controller_xinput"))]
         self.pre_post.pre_update(
             timing,
@@ -501,12 +843,19 @@ impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> System for Sc
             match result {
                 SceneUpdateResult::Nothing => {}
                 SceneUpdateResult::Push(pop_current, name) => {
+                    self.scenes_changed = true;
                     if pop_current {
                         self.scenes.pop();
                     }
                     (self.scene_switcher)(&self.style, &mut self.scenes, name);
+                    if let (Some((width, height)), Some(scene)) =
+                        (self.canvas_size, self.scenes.last_mut())
+                    {
+                        scene.on_canvas_resized(width, height);
+                    }
                 }
                 SceneUpdateResult::Pop(result) => {
+                    self.scenes_changed = true;
                     self.scenes.pop();
                     if let Some(previous) = self.scenes.last_mut() {
                         previous.resuming(result);
@@ -537,6 +886,10 @@ impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> System for Sc
     }
 
     fn render(&mut self, graphics: &mut Graphics) {
+        self.render_interpolated(graphics, 0.0);
+    }
+
+    fn render_interpolated(&mut self, graphics: &mut Graphics, alpha: f64) {
         #[cfg(any(feature = "controller", feature = "controller_xinput"))]
         self.pre_post.pre_render(
             graphics,
@@ -554,25 +907,43 @@ impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> System for Sc
                     None => graphics.clear(BLACK),
                     Some(i) => {
                         #[cfg(any(feature = "controller", feature = "controller_xinput"))]
-                        self.scenes[i].render(
+                        self.scenes[i].render_interpolated(
                             graphics,
                             &self.mouse,
                             &self.held_keys,
                             &self.controller,
+                            alpha,
                         );
                         #[cfg(not(any(feature = "controller", feature = "controller_xinput")))]
-                        self.scenes[i].render(graphics, &self.mouse, &self.held_keys);
+                        self.scenes[i].render_interpolated(
+                            graphics,
+                            &self.mouse,
+                            &self.held_keys,
+                            alpha,
+                        );
                     }
                 }
                 #[cfg(any(feature = "controller", feature = "controller_xinput"))]
-                active.render(graphics, &self.mouse, &self.held_keys, &self.controller);
+                active.render_interpolated(
+                    graphics,
+                    &self.mouse,
+                    &self.held_keys,
+                    &self.controller,
+                    alpha,
+                );
                 #[cfg(not(any(feature = "controller", feature = "controller_xinput")))]
-                active.render(graphics, &self.mouse, &self.held_keys);
+                active.render_interpolated(graphics, &self.mouse, &self.held_keys, alpha);
             } else {
                 #[cfg(any(feature = "controller", feature = "controller_xinput"))]
-                active.render(graphics, &self.mouse, &self.held_keys, &self.controller);
+                active.render_interpolated(
+                    graphics,
+                    &self.mouse,
+                    &self.held_keys,
+                    &self.controller,
+                    alpha,
+                );
                 #[cfg(not(any(feature = "controller", feature = "controller_xinput")))]
-                active.render(graphics, &self.mouse, &self.held_keys);
+                active.render_interpolated(graphics, &self.mouse, &self.held_keys, alpha);
             }
         }
         #[cfg(any(feature = "controller", feature = "controller_xinput"))]
@@ -586,10 +957,12 @@ impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> System for Sc
         #[cfg(not(any(feature = "controller", feature = "controller_xinput")))]
         self.pre_post
             .post_render(graphics, &self.mouse, &self.held_keys, &mut self.scenes);
+        self.scenes_changed = false;
     }
 
     fn on_mouse_move(&mut self, mouse: &MouseData) {
         self.mouse.xy = mouse.xy;
+        self.clicks.moved(self.mouse.xy, &self.click_options);
         if self.mouse.any_held() {
             if let Some(active) = self.scenes.last_mut() {
                 active.on_mouse_drag(&self.mouse, &self.held_keys)
@@ -600,6 +973,7 @@ impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> System for Sc
     fn on_mouse_down(&mut self, mouse: &MouseData, button: MouseButton) {
         self.mouse.xy = mouse.xy;
         self.mouse.add_down(self.mouse.xy, button);
+        self.clicks.down(button, self.mouse.xy, self.event_time());
         if let Some(active) = self.scenes.last_mut() {
             active.on_mouse_down(&self.mouse, button, &self.held_keys);
         }
@@ -607,10 +981,17 @@ impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> System for Sc
 
     fn on_mouse_up(&mut self, mouse: &MouseData, button: MouseButton) {
         self.mouse.xy = mouse.xy;
+        let now = self.event_time();
         if let Some(active) = self.scenes.last_mut() {
             active.on_mouse_up(&self.mouse, button, &self.held_keys);
             if let Some(down) = self.mouse.is_down(button) {
+                let count = self
+                    .clicks
+                    .up(button, self.mouse.xy, now, &self.click_options);
                 active.on_mouse_click(down, &self.mouse, button, &self.held_keys);
+                if count > 1 {
+                    active.on_mouse_multi_click(down, &self.mouse, button, count, &self.held_keys);
+                }
             }
             self.mouse.add_up(button);
         }
@@ -623,6 +1004,12 @@ impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> System for Sc
         }
     }
 
+    fn on_mouse_motion(&mut self, _: &MouseData, x_diff: f64, y_diff: f64) {
+        if let Some(active) = self.scenes.last_mut() {
+            active.on_mouse_motion(&self.mouse, x_diff, y_diff, &self.held_keys);
+        }
+    }
+
     fn on_key_down(&mut self, keys: Vec<KeyCode>) {
         for key in keys {
             self.held_keys.insert(key);
@@ -632,6 +1019,14 @@ impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> System for Sc
         }
     }
 
+    fn on_key_repeat(&mut self, keys: Vec<KeyCode>) {
+        for key in keys {
+            if let Some(active) = self.scenes.last_mut() {
+                active.on_key_repeat(key, &self.mouse, &self.held_keys);
+            }
+        }
+    }
+
     fn on_key_up(&mut self, keys: Vec<KeyCode>) {
         for key in keys {
             self.held_keys.remove(&key);
@@ -641,7 +1036,252 @@ impl<SR: Clone + PartialEq + Debug, SN: Clone + PartialEq + Debug> System for Sc
         }
     }
 
+    fn on_canvas_resized(&mut self, width: usize, height: usize) {
+        self.canvas_size = Some((width, height));
+        self.pre_post.on_canvas_resized(width, height);
+        for scene in &mut self.scenes {
+            scene.on_canvas_resized(width, height);
+        }
+    }
+
     fn should_exit(&mut self) -> bool {
         self.should_exit
     }
+
+    fn window_commands(&mut self) -> Vec<WindowCommand> {
+        let mut commands = self.pre_post.window_commands();
+        if let Some(active) = self.scenes.last_mut() {
+            commands.extend(active.window_commands());
+        }
+        commands
+    }
+
+    fn on_touch(&mut self, _: &MouseData, touch: TouchData) {
+        if let Some(active) = self.scenes.last_mut() {
+            active.on_touch(touch, &self.mouse, &self.held_keys);
+        }
+    }
+
+    fn on_file_hovered(&mut self, mouse: &MouseData, path: PathBuf) {
+        self.mouse.xy = mouse.xy;
+        if let Some(active) = self.scenes.last_mut() {
+            active.on_file_hovered(path, &self.mouse, &self.held_keys);
+        }
+    }
+
+    fn on_file_hover_cancelled(&mut self) {
+        if let Some(active) = self.scenes.last_mut() {
+            active.on_file_hover_cancelled();
+        }
+    }
+
+    fn on_file_dropped(&mut self, mouse: &MouseData, path: PathBuf) {
+        self.mouse.xy = mouse.xy;
+        if let Some(active) = self.scenes.last_mut() {
+            active.on_file_dropped(path, &self.mouse, &self.held_keys);
+        }
+    }
+
+    fn on_modifiers_changed(&mut self, modifiers: Modifiers) {
+        self.mouse.modifiers = modifiers;
+    }
+
+    fn on_focus_changed(&mut self, focused: bool) {
+        //key and button releases aren't sent while unfocused so forget them
+        if !focused {
+            self.held_keys.clear();
+            self.mouse.clear_buttons();
+            self.clicks.clear();
+        }
+    }
+
+    fn on_suspend(&mut self) {
+        if let Some(active) = self.scenes.last_mut() {
+            active.on_suspend();
+        }
+    }
+
+    fn on_resume(&mut self) {
+        if let Some(active) = self.scenes.last_mut() {
+            active.on_resume();
+        }
+    }
+
+    fn on_close_requested(&mut self) -> bool {
+        self.scenes
+            .last_mut()
+            .map(|active| active.on_close_requested())
+            .unwrap_or(true)
+    }
+
+    fn on_error(&mut self, error: &GraphicsError) {
+        self.pre_post.on_error(error);
+        if let Some(active) = self.scenes.last_mut() {
+            active.on_error(error);
+        }
+    }
+
+    fn software_cursor(&self) -> Option<CursorKind> {
+        self.scenes
+            .last()
+            .and_then(|active| active.software_cursor())
+    }
+
+    fn debug_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("Scenes: {}", self.scenes.len())];
+        for (i, scene) in self.scenes.iter().enumerate().rev() {
+            let dialog = if scene.is_dialog() { " (dialog)" } else { "" };
+            lines.push(format!(" {i}: id {}{dialog}", scene.id()));
+        }
+        lines
+    }
+
+    fn needs_redraw(&mut self) -> bool {
+        match self.scenes.last_mut() {
+            Some(active) => active.needs_redraw() || self.scenes_changed,
+            None => true,
+        }
+    }
+
+    fn wakeup_requested(&mut self) -> Option<Wakeup> {
+        //cleared once the new scene has been rendered
+        if self.scenes_changed {
+            return Some(Wakeup::Now);
+        }
+        let requested = self
+            .scenes
+            .last_mut()
+            .and_then(|active| active.wakeup_requested());
+        //long presses are only detected when updating
+        match (requested, self.clicks.next_long_press(&self.click_options)) {
+            (Some(Wakeup::At(at)), Some(long_press)) => Some(Wakeup::At(at.min(long_press))),
+            (None, Some(long_press)) => Some(Wakeup::At(long_press)),
+            (requested, _) => requested,
+        }
+    }
+
+    fn on_input_log(&mut self, log: InputLog) {
+        self.pre_post.on_input_log(log);
+    }
+
+    fn screenshot_requested(&mut self) -> Option<ScreenshotSource> {
+        self.scenes
+            .last_mut()
+            .and_then(|active| active.screenshot_requested())
+    }
+
+    fn on_screenshot(&mut self, source: ScreenshotSource, image: Image) {
+        if let Some(active) = self.scenes.last_mut() {
+            active.on_screenshot(source, image);
+        }
+    }
+
+    #[cfg(feature = "images")]
+    fn recording_requested(&mut self) -> Option<RecordingRequest> {
+        self.scenes
+            .last_mut()
+            .and_then(|active| active.recording_requested())
+    }
+
+    #[cfg(feature = "images")]
+    fn on_recording_finished(&mut self, result: Result<PathBuf, GraphicsError>) {
+        if let Some(active) = self.scenes.last_mut() {
+            active.on_recording_finished(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn multi_clicks_and_long_press() {
+        let options = ClickOptions::default();
+        let mut clicks = ClickTracker::default();
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let xy = coord!(10, 10);
+
+        clicks.down(MouseButton::Left, xy, at(0));
+        assert_eq!(clicks.up(MouseButton::Left, xy, at(50), &options), 1);
+        clicks.down(MouseButton::Left, xy, at(100));
+        assert_eq!(clicks.up(MouseButton::Left, xy, at(150), &options), 2);
+        clicks.down(MouseButton::Left, xy, at(200));
+        assert_eq!(clicks.up(MouseButton::Left, xy, at(250), &options), 3);
+        assert_eq!(
+            clicks.up(MouseButton::Left, coord!(30, 10), at(300), &options),
+            1
+        );
+        assert_eq!(clicks.up(MouseButton::Left, xy, at(2000), &options), 1);
+
+        clicks.down(MouseButton::Right, xy, at(3000));
+        assert!(clicks.long_presses(at(3500), &options).is_empty());
+        assert_eq!(
+            clicks.long_presses(at(3900), &options),
+            vec![MouseButton::Right]
+        );
+        assert!(clicks.long_presses(at(4000), &options).is_empty());
+
+        clicks.down(MouseButton::Right, xy, at(5000));
+        clicks.moved(coord!(20, 20), &options);
+        assert!(clicks.long_presses(at(6000), &options).is_empty());
+    }
+
+    #[cfg(not(any(feature = "controller", feature = "controller_xinput")))]
+    #[test]
+    fn long_press_wakes_runner() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        struct Pressable(Rc<RefCell<Vec<MouseButton>>>);
+
+        impl Scene<(), ()> for Pressable {
+            fn on_mouse_long_press(
+                &mut self,
+                _: &MouseData,
+                button: MouseButton,
+                _: &FxHashSet<KeyCode>,
+            ) {
+                self.0.borrow_mut().push(button);
+            }
+
+            fn update(
+                &mut self,
+                _: &Timing,
+                _: &MouseData,
+                _: &FxHashSet<KeyCode>,
+                _: Option<&Window>,
+            ) -> SceneUpdateResult<(), ()> {
+                SceneUpdateResult::Nothing
+            }
+        }
+
+        let presses = Rc::new(RefCell::new(vec![]));
+        let mut host = SceneHost::new(
+            Box::new(Pressable(presses.clone())),
+            None,
+            |_, _, _| {},
+            UiStyle::default(),
+            ClickOptions::default(),
+            empty_pre_post(),
+        )
+        .unwrap();
+        let mut timing = Timing::new(240);
+        host.update(&timing, None);
+        assert_eq!(host.wakeup_requested(), None);
+
+        host.on_mouse_down(&MouseData::default(), MouseButton::Left);
+        let Some(Wakeup::At(at)) = host.wakeup_requested() else {
+            panic!("long press didn't request a wakeup");
+        };
+        assert!(at >= timing.now + Duration::from_millis(800));
+
+        //with ControlFlow::Wait the next update is when the runner wakes
+        timing.now = at;
+        host.update(&timing, None);
+        assert_eq!(*presses.borrow(), vec![MouseButton::Left]);
+        assert_eq!(host.wakeup_requested(), None);
+    }
 }
@@ -252,12 +252,24 @@ impl DirPanel {
     }
 
     pub fn on_mouse_click(&mut self, down: Coord, up: Coord) -> Option<DirResult> {
+        if self.state == ViewState::Disabled || !self.bounds.contains(down) {
+            return None;
+        }
+        self.entry_at(up)
+    }
+
+    /// Returns the entry that was double clicked, folders should be opened and files used
+    pub fn on_mouse_double_click(&mut self, xy: Coord) -> Option<DirResult> {
         if self.state == ViewState::Disabled {
             return None;
         }
-        if self.bounds.contains(down) && self.bounds.contains(up) {
+        self.entry_at(xy)
+    }
+
+    fn entry_at(&self, xy: Coord) -> Option<DirResult> {
+        if self.bounds.contains(xy) {
             for i in 0..self.entry_visible_count {
-                if self.bounds_for_row(i).contains(up) {
+                if self.bounds_for_row(i).contains(xy) {
                     return self
                         .files
                         .get(i + self.first_visible_file_index)
@@ -41,7 +41,12 @@ pub mod prelude {
 /// Can be used to render multiple views easily
 /// # Usage
 /// ```rust
+///# use pixels_graphics_lib::prelude::*;
+///# use pixels_graphics_lib::render;
+///# use pixels_graphics_lib::ui::prelude::*;
+///# fn example(graphics: &mut Graphics, mouse: &MouseData, view1: &Button, view2: &Button) {
 /// render!(graphics, mouse, view1, view2);
+///# }
 /// ```
 #[macro_export]
 macro_rules! render {
@@ -294,6 +294,35 @@ impl TextField {
         false
     }
 
+    /// Select the word at `xy`
+    pub fn on_mouse_double_click(&mut self, xy: Coord) -> bool {
+        if self.state == ViewState::Disabled || !self.bounds.contains(xy) {
+            return false;
+        }
+        self.focused = true;
+        let chars: Vec<char> = self.content.chars().collect();
+        let is_word = |i: usize| chars.get(i).map(|c| c.is_alphanumeric() || *c == '_');
+        let pos = self
+            .cursor_pos_for_x(xy.x)
+            .min(chars.len().saturating_sub(1));
+        if is_word(pos) != Some(true) {
+            self.cursor_pos = self.cursor_pos_for_x(xy.x);
+            self.selection = None;
+            return true;
+        }
+        let mut start = pos;
+        while start > 0 && is_word(start - 1) == Some(true) {
+            start -= 1;
+        }
+        let mut end = pos;
+        while is_word(end + 1) == Some(true) {
+            end += 1;
+        }
+        self.cursor_pos = end + 1;
+        self.selection = Some(start..=end);
+        true
+    }
+
     pub fn on_mouse_drag(&mut self, down: Coord, up: Coord) {
         if self.state != ViewState::Disabled
             && self.bounds.contains(down)
@@ -333,15 +362,14 @@ impl TextField {
 
     fn grow_selection_right(&mut self) {}
 
-    pub fn on_key_press(&mut self, key: KeyCode, held_keys: &FxHashSet<KeyCode>) {
+    /// Call this for key down and key repeat events so held keys (such as Backspace) repeat
+    pub fn on_key_press(&mut self, key: KeyCode, modifiers: Modifiers) {
         if !self.focused || self.state == ViewState::Disabled {
             return;
         }
         match key {
             KeyCode::ArrowLeft => {
-                if held_keys.contains(&KeyCode::ShiftRight)
-                    || held_keys.contains(&KeyCode::ShiftLeft)
-                {
+                if modifiers.shift {
                     self.grow_selection_left();
                 } else {
                     self.collapse_selection();
@@ -356,9 +384,7 @@ impl TextField {
                 }
             }
             KeyCode::ArrowRight => {
-                if held_keys.contains(&KeyCode::ShiftRight)
-                    || held_keys.contains(&KeyCode::ShiftLeft)
-                {
+                if modifiers.shift {
                     self.grow_selection_right();
                 } else {
                     self.collapse_selection();
@@ -407,10 +433,8 @@ impl TextField {
             _ => {
                 if let Some((lower, upper)) = key_code_to_char(key) {
                     self.delete_selection();
-                    let shift_pressed = held_keys.contains(&KeyCode::ShiftLeft)
-                        || held_keys.contains(&KeyCode::ShiftRight);
                     for filter in &self.filters {
-                        let char = if shift_pressed { upper } else { lower };
+                        let char = if modifiers.shift { upper } else { lower };
                         if filter.is_char_allowed(char) {
                             if !self.is_full() {
                                 self.content.insert(self.cursor_pos, char);